@@ -0,0 +1,46 @@
+//! Search without threads or the system clock, as needed on
+//! `wasm32-unknown-unknown`
+//!
+//! ```sh
+//! cargo build -p gomoku_lib --example single_thread --no-default-features \
+//!   --target wasm32-unknown-unknown
+//! ```
+//!
+//! In the browser the clock would call `performance.now()` through
+//! wasm-bindgen instead of counting its own calls.
+
+use std::{
+  sync::atomic::{AtomicU64, Ordering},
+  time::Duration,
+};
+
+use gomoku_lib::{Board, Engine, Player, TilePointer};
+
+/// Fake clock advancing by 10 µs on every call
+fn clock() -> Duration {
+  static TICKS: AtomicU64 = AtomicU64::new(0);
+
+  Duration::from_micros(10 * TICKS.fetch_add(1, Ordering::Relaxed))
+}
+
+fn main() {
+  let engine = Engine::builder()
+    .time_limit(Duration::from_millis(100))
+    .clock(clock)
+    .build()
+    .expect("engine without a thread pool always builds");
+
+  let mut board = Board::default();
+  board.set_tile(TilePointer { x: 7, y: 7 }, Some(Player::X));
+
+  let outcome = engine
+    .decide(&mut board, Player::O)
+    .expect("the game has just started");
+
+  if let Some(best) = outcome.best {
+    println!(
+      "best move {} after depth {}",
+      best.tile, outcome.stats.depth_reached
+    );
+  }
+}
@@ -1,15 +1,31 @@
+#[cfg(feature = "bitboard")]
+mod bitboard;
 mod error;
 pub(crate) mod evaluation;
 mod sequences;
-
-use std::{fmt, str::FromStr, sync::OnceLock};
-
-pub use error::Error;
-use evaluation::{shape_score, Eval};
+mod symmetry;
+mod threats;
+
+#[cfg(feature = "bitboard")]
+use bitboard::Bitboard;
+use std::{
+  collections::HashMap,
+  fmt, iter,
+  ops::Index,
+  str::FromStr,
+  sync::{Mutex, OnceLock},
+};
+
+pub use error::{Error, PlaceError, ReplayError};
+pub use evaluation::Heuristic;
+use evaluation::{clamp_score, Eval, EvalCache};
 use sequences::{generate, Sequence, Sequences};
+pub use sequences::{SeqInfo, SeqKind};
+pub(crate) use symmetry::Symmetry;
+pub use threats::{DoubleThreat, Threat, Threats};
 
-use super::{Player, Score};
-use crate::state::State;
+use super::{Move, Player, Rules, Score};
+use crate::{state::State, utils::splitmix64};
 
 /// Represents a tile on the board.
 ///
@@ -20,6 +36,12 @@ pub type Tile = Option<Player>;
 /// Represents a pointer to a tile on the board.
 ///
 /// Doesn't provide any bounds checking or other guarantees.
+///
+/// Coordinates are 0-based, but the text form uses the usual gomoku notation
+/// of a letter column followed by a 1-based row, so `x: 7, y: 7` is written
+/// as `h8`. Columns past `z` continue as `aa`, `ab`, etc.
+/// [`Display`](fmt::Display) and [`FromStr`] use this form and round-trip.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub struct TilePointer {
   /// x coordinate
@@ -27,24 +49,111 @@ pub struct TilePointer {
   /// y coordinate
   pub y: u8,
 }
+impl TilePointer {
+  /// Directions of the four lines going through every tile, for use with
+  /// [`TilePointer::offset`]
+  pub(crate) const DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+  /// Get the spreadsheet-style name of a column (`a`, ..., `z`, `aa`, `ab`,
+  /// ...).
+  pub fn column_name(x: u8) -> String {
+    let mut name = String::new();
+    let mut x = usize::from(x) + 1;
+
+    while x > 0 {
+      x -= 1;
+      name.insert(0, char::from(b'a' + (x % 26) as u8));
+      x /= 26;
+    }
+
+    name
+  }
+
+  /// Parse a spreadsheet-style column name into its index.
+  ///
+  /// Returns `None` if the name is empty, contains characters other than
+  /// ASCII letters or doesn't fit into `u8`.
+  pub fn column_from_name(name: &str) -> Option<u8> {
+    if name.is_empty() {
+      return None;
+    }
+
+    name
+      .bytes()
+      .try_fold(0usize, |acc, c| {
+        if !c.is_ascii_alphabetic() {
+          return None;
+        }
+
+        let digit = usize::from(c.to_ascii_lowercase() - b'a') + 1;
+        let value = acc * 26 + digit;
+
+        (value <= usize::from(u8::MAX) + 1).then_some(value)
+      })
+      .map(|value| (value - 1) as u8)
+  }
+
+  /// Get the tile shifted by `dx` and `dy`.
+  ///
+  /// Returns `None` if the result is outside of a board of the given
+  /// dimensions.
+  pub fn offset(self, dx: i8, dy: i8, width: u8, height: u8) -> Option<TilePointer> {
+    let x = self.x.checked_add_signed(dx)?;
+    let y = self.y.checked_add_signed(dy)?;
+
+    (x < width && y < height).then_some(TilePointer { x, y })
+  }
+
+  /// Get iterator over the up to 8 orthogonally and diagonally adjacent
+  /// tiles on a board of the given dimensions.
+  pub fn neighbors(self, width: u8, height: u8) -> impl Iterator<Item = TilePointer> {
+    const OFFSETS: [(i8, i8); 8] = [
+      (-1, -1),
+      (0, -1),
+      (1, -1),
+      (-1, 0),
+      (1, 0),
+      (-1, 1),
+      (0, 1),
+      (1, 1),
+    ];
+
+    OFFSETS
+      .into_iter()
+      .filter_map(move |(dx, dy)| self.offset(dx, dy, width, height))
+  }
+}
 impl TryFrom<&str> for TilePointer {
   type Error = Box<dyn std::error::Error>;
 
   fn try_from(value: &str) -> Result<Self, Self::Error> {
-    let mut chars = value.chars();
+    let split = value
+      .find(|c: char| !c.is_ascii_alphabetic())
+      .unwrap_or(value.len());
+    let (column, row) = value.split_at(split);
 
-    let x = chars.next().ok_or::<Self::Error>("No input".into())?;
-    let y = chars.collect::<String>().parse::<u8>()?;
+    let x = Self::column_from_name(column).ok_or::<Self::Error>("Invalid column".into())?;
+    let y = row.parse::<u16>()?;
 
-    let x = x as u8 - b'a';
-    let y = y - 1;
+    let y = y
+      .checked_sub(1)
+      .ok_or::<Self::Error>("Rows start at 1".into())?;
+    let y = u8::try_from(y)?;
 
     Ok(TilePointer { x, y })
   }
 }
+impl FromStr for TilePointer {
+  type Err = Box<dyn std::error::Error>;
+
+  /// Parse a tile in the `h8` form, see [`TilePointer`].
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    TilePointer::try_from(s)
+  }
+}
 impl fmt::Debug for TilePointer {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "{}{}", (self.x + b'a') as char, self.y + 1)
+    write!(f, "{}{}", Self::column_name(self.x), u16::from(self.y) + 1)
   }
 }
 impl fmt::Display for TilePointer {
@@ -53,106 +162,521 @@ impl fmt::Display for TilePointer {
   }
 }
 
-/// Cached sequences for very fast board access
-// HACK: Relies on the fact that the board size is the same thoroughout the
-// whole runtime. This is good enough for now, but **should** be refactored in
-// the future.
-static SEQUENCES: OnceLock<Sequences> = OnceLock::new();
+/// Token for taking back a move made by [`Board::apply`]
+///
+/// Remembers the evaluation of the lines through the tile from before the
+/// move, so taking it back doesn't need to evaluate them again.
+#[derive(Debug)]
+#[must_use = "the move can be taken back only with the token"]
+pub struct Undo {
+  ptr: TilePointer,
+  /// Evaluation of every sequence through the tile, by sequence index
+  evals: [(usize, Eval); 4],
+  /// Last move from before this one
+  last_move: Option<TilePointer>,
+}
+impl Undo {
+  /// Get the tile of the move.
+  pub fn tile(&self) -> TilePointer {
+    self.ptr
+  }
+}
 
-fn initialize_sequences(board_size: u8) {
-  let sequences = SEQUENCES.get_or_init(|| generate(board_size));
+/// Cached sequences for very fast board access, one table per board shape
+///
+/// The tables are never freed, but there is only a handful of shapes used in
+/// practice.
+static SEQUENCES: OnceLock<Mutex<HashMap<(u8, u8), &'static Sequences>>> = OnceLock::new();
+
+fn initialize_sequences(width: u8, height: u8) -> &'static Sequences {
+  let mut cache = SEQUENCES
+    .get_or_init(Mutex::default)
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+  cache
+    .entry((width, height))
+    .or_insert_with(|| Box::leak(Box::new(generate(width, height))))
+}
 
-  assert_eq!(
-    sequences.len(),
-    6 * board_size as usize - 2,
-    "Incompatible board size and sequences",
-  );
+/// Random key of a stone of `player` at the given index, for Zobrist hashing
+fn zobrist_key(index: usize, player: Player) -> u64 {
+  splitmix64(((index as u64) << 1) | u64::from(player == Player::O))
 }
 
 /// A Gomoku board.
 ///
-/// The board is guaranteed to be a square and at least 9x9.
-#[derive(Clone, PartialEq, Eq, Debug)]
+/// The board is guaranteed to be a rectangle. Boards created from tiles have
+/// both sides at least [`Board::MIN_SIZE`] tiles long, unless created by
+/// [`Board::new_unchecked_size`].
+///
+/// Sides can be up to 255 tiles long and everything works at any size, with
+/// columns past `z` named `aa`, `ab` and so on. The search however considers
+/// every empty tile, so above about 32x32 it gets too slow to reach a useful
+/// depth in a few seconds.
+#[derive(Clone)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(try_from = "RawBoard", into = "RawBoard")
+)]
 pub struct Board {
-  size: u8,
+  width: u8,
+  height: u8,
   data: Box<[Tile]>,
+  evals: EvalCache,
+  sequences: &'static Sequences,
+  /// Zobrist hash of the tiles, updated on every change
+  hash: u64,
+  /// Bit set of the empty tiles by index, updated on every change
+  empty: Box<[u64]>,
+  /// Stones of both players for the bitboard win detection, updated on every
+  /// change
+  #[cfg(feature = "bitboard")]
+  bits: Bitboard,
+  /// Tile of the last stone placed, for display only
+  last_move: Option<TilePointer>,
 }
 
 impl Board {
-  /// Create a new board from a 2D vector of tiles.
+  /// Size of the board used by [`Board::default`], the usual 15x15
+  pub const DEFAULT_SIZE: u8 = 15;
+
+  /// Minimum length of both sides of a board accepted by [`Board::new`] and
+  /// the other constructors
+  ///
+  /// A five fits on smaller boards too, but the engine needs some room around
+  /// the lines to play well.
+  pub const MIN_SIZE: u8 = 9;
+
+  /// Create a new board from a 2D vector of tiles, row by row.
   ///
-  /// The board must be a square and at least 9x9.
+  /// All rows must have the same length and both sides must be at least
+  /// [`Board::MIN_SIZE`] tiles long.
   ///
   /// # Errors
-  /// Returns an error if the board is not a square or is too small.
+  /// Returns an error if the board is not a rectangle, is too small or too
+  /// large.
   pub fn new(data: Vec<Vec<Tile>>) -> Result<Board, Error> {
-    if data.len() <= 8 {
-      return Err(Error::TooSmall { size: data.len() });
+    let size = data.len().min(data.first().map_or(0, Vec::len));
+
+    if size < usize::from(Self::MIN_SIZE) {
+      return Err(Error::TooSmall { size });
+    }
+
+    Self::new_unchecked_size(data)
+  }
+
+  /// Same as [`Board::new`], but accepts any board with at least one tile,
+  /// e.g. for small test fixtures.
+  ///
+  /// The win length stays five, so lines shorter than that can never be won
+  /// and a board with both sides shorter than five always ends in a draw.
+  ///
+  /// # Errors
+  /// Returns an error if the board is not a rectangle, is empty or either
+  /// side is longer than [`u8::MAX`].
+  pub fn new_unchecked_size(data: Vec<Vec<Tile>>) -> Result<Board, Error> {
+    let height = data.len();
+    let width = data.first().map_or(0, Vec::len);
+
+    if height.min(width) == 0 {
+      return Err(Error::TooSmall { size: 0 });
+    }
+
+    if height.max(width) > usize::from(u8::MAX) {
+      return Err(Error::TooLarge {
+        size: height.max(width),
+      });
     }
 
     for (index, row) in data.iter().enumerate() {
-      if row.len() != data.len() {
-        return Err(Error::NotSquare {
-          height: data.len(),
+      if row.len() != width {
+        return Err(Error::NotRectangular {
+          expected: width,
           line: index + 1,
           width: row.len(),
         });
       }
     }
 
-    let board_size = data.len() as u8;
     let flat_data = data.into_iter().flatten().collect();
 
-    initialize_sequences(board_size);
+    Ok(Board::from_flat(width as u8, height as u8, flat_data))
+  }
 
-    Ok(Board {
-      data: flat_data,
-      size: board_size,
-    })
+  /// Create an empty square board of the given size.
+  ///
+  /// # Errors
+  /// Returns an error if the size is below [`Board::MIN_SIZE`].
+  pub fn new_empty(size: u8) -> Result<Board, Error> {
+    Board::new_rect(size, size)
+  }
+
+  /// Create an empty board with the given dimensions.
+  ///
+  /// # Errors
+  /// Returns an error if either side is below [`Board::MIN_SIZE`].
+  pub fn new_rect(width: u8, height: u8) -> Result<Board, Error> {
+    let size = width.min(height);
+
+    if size < Self::MIN_SIZE {
+      return Err(Error::TooSmall {
+        size: usize::from(size),
+      });
+    }
+
+    let data = vec![None; usize::from(width) * usize::from(height)].into_boxed_slice();
+
+    Ok(Board::from_flat(width, height, data))
+  }
+
+  /// Create a new board from a 2D vector of characters.
+  ///
+  /// Uses the same convention as [`Board::from_str`], `'x'` and `'o'` for the
+  /// players and `'-'` for an empty tile, but rejects any other character.
+  ///
+  /// # Errors
+  /// Returns an error if there is an invalid character or the board is not a
+  /// rectangle or is too small.
+  pub fn try_from_chars(data: Vec<Vec<char>>) -> Result<Board, Error> {
+    Board::parse_rows(data, &['-'])
+  }
+
+  /// Parse a string into a board, rejecting unknown characters.
+  ///
+  /// Unlike [`Board::from_str`], which reads anything other than `'x'` and
+  /// `'o'` as an empty tile, only `'-'`, `'.'` and `' '` are accepted as
+  /// empty. This catches boards pasted together with coordinate labels or
+  /// other decorations, which would otherwise change the parsed size.
+  ///
+  /// # Errors
+  /// Returns an error with the line and column of the first invalid character
+  /// or if the board is not a rectangle or is too small.
+  pub fn from_str_strict(input: &str) -> Result<Board, Error> {
+    Board::parse_rows(input.lines().map(str::chars), &['-', '.', ' '])
+  }
+
+  fn parse_rows<R>(rows: impl IntoIterator<Item = R>, empty: &[char]) -> Result<Board, Error>
+  where
+    R: IntoIterator<Item = char>,
+  {
+    let tiles = rows
+      .into_iter()
+      .enumerate()
+      .map(|(y, row)| {
+        row
+          .into_iter()
+          .enumerate()
+          .map(|(x, value)| match value {
+            'x' | 'X' => Ok(Some(Player::X)),
+            'o' | 'O' => Ok(Some(Player::O)),
+            _ if empty.contains(&value) => Ok(None),
+            _ => Err(Error::InvalidTile {
+              line: y + 1,
+              column: x + 1,
+              value,
+            }),
+          })
+          .collect()
+      })
+      .collect::<Result<_, _>>()?;
+
+    Board::new(tiles)
+  }
+
+  /// Parse a board from a shortened FEN string.
+  ///
+  /// See [`parse_fen_string`](crate::utils::parse_fen_string) for the format
+  /// and [`to_fen_string`](crate::utils::to_fen_string) for the inverse. The
+  /// side to move and ply of the extended form are ignored.
+  ///
+  /// # Errors
+  /// Returns an error if the string is malformed or contains an invalid tile.
+  #[cfg(feature = "fen")]
+  pub fn from_fen(input: &str) -> Result<Board, Box<dyn std::error::Error>> {
+    crate::utils::parse_fen_with_state(input).map(|(board, ..)| board)
+  }
+
+  /// Create a square board of the given size by playing the moves in order.
+  ///
+  /// Checks that the players alternate, see [`Board::replay_with`] to allow
+  /// any order.
+  ///
+  /// # Errors
+  /// Returns an error with the index of the first move that is illegal, is
+  /// played out of turn or is played after the game ended.
+  pub fn replay(size: u8, moves: &[(Player, TilePointer)]) -> Result<Board, ReplayError> {
+    Board::replay_with(size, moves, true)
+  }
+
+  /// Same as [`Board::replay`], but checks that the players alternate only
+  /// if `alternate` is true.
+  ///
+  /// # Errors
+  /// Returns an error with the index of the first move that is illegal, is
+  /// played out of turn or is played after the game ended.
+  pub fn replay_with(
+    size: u8,
+    moves: &[(Player, TilePointer)],
+    alternate: bool,
+  ) -> Result<Board, ReplayError> {
+    let mut board = Board::new_empty(size).map_err(ReplayError::InvalidBoard)?;
+    let mut last_player = None;
+
+    for (ply, &(player, ptr)) in moves.iter().enumerate() {
+      if board.winner().is_some() {
+        return Err(ReplayError::GameEnded { ply });
+      }
+
+      if alternate && last_player == Some(player) {
+        return Err(ReplayError::WrongPlayer {
+          ply,
+          expected: !player,
+        });
+      }
+
+      board
+        .place(ptr, player)
+        .map_err(|error| ReplayError::Illegal { ply, error })?;
+
+      last_player = Some(player);
+    }
+
+    Ok(board)
   }
 
-  /// Create an empty board of the given size.
-  pub fn new_empty(size: u8) -> Board {
-    let data = vec![None; size.pow(2) as usize].into_boxed_slice();
+  /// Create a board from already validated flat data and evaluate it.
+  fn from_flat(width: u8, height: u8, data: Box<[Tile]>) -> Board {
+    let mut board = Board {
+      width,
+      height,
+      data,
+      evals: EvalCache::default(),
+      sequences: initialize_sequences(width, height),
+      hash: 0,
+      empty: Box::default(),
+      #[cfg(feature = "bitboard")]
+      bits: Bitboard::new(width, height),
+      last_move: None,
+    };
+
+    board.hash = board.compute_zobrist();
+    board.empty = board.compute_empty();
+
+    #[cfg(feature = "bitboard")]
+    for index in 0..board.data.len() {
+      if let Some(player) = board.data[index] {
+        let TilePointer { x, y } = board.get_ptr_from_index(index);
+        board.bits.toggle(x, y, player);
+      }
+    }
+
+    let evals = board
+      .sequences()
+      .iter()
+      .map(|seq| board.evaluate_sequence(seq, &Heuristic::DEFAULT))
+      .collect();
 
-    initialize_sequences(size);
+    board.evals = EvalCache::new(evals);
 
-    Board { size, data }
+    board
   }
 
   /// Get a reference to the sequences table.
+  pub fn sequences(&self) -> &'static Sequences {
+    self.sequences
+  }
+
+  /// Get the sequences table together with the direction and position of
+  /// each sequence.
+  pub fn sequences_with_info(&self) -> impl Iterator<Item = (SeqInfo, &'static Sequence)> + '_ {
+    self
+      .sequences()
+      .iter()
+      .enumerate()
+      .map(|(index, sequence)| (self.sequence_info(index), sequence))
+  }
+
+  /// Get the direction and position of the sequence at `index` in the
+  /// sequences table.
   ///
   /// # Panics
-  /// Panics if the sequences table has not been initialized.
-  pub fn sequences(&self) -> &'static Sequences {
-    SEQUENCES.get().expect("Sequences are initialized")
+  /// Panics if `index` is out of bounds.
+  pub fn sequence_info(&self, index: usize) -> SeqInfo {
+    assert!(
+      index < self.sequences().len(),
+      "sequence {index} out of bounds"
+    );
+
+    SeqInfo::from_position(index, self.width, self.height)
   }
 
   /// Get sequences relevant for the given tile.
   ///
-  /// Relevant means the column, row and both diagonals that include the tile.
+  /// Relevant means the row, column and both diagonals that include the tile,
+  /// in the order of [`SeqKind::ALL`].
   pub fn relevant_sequences(&self, ptr: TilePointer) -> [&Sequence; 4] {
-    let n = self.size;
-    let TilePointer { x, y } = ptr;
-
     let sequences = self.sequences();
 
-    [
-      &sequences[usize::from(y)],                       // row
-      &sequences[usize::from(n + x)],                   // column
-      &sequences[usize::from(2 * n + x + y)],           // diagonal
-      &sequences[usize::from((4 * n - 2) + n + y - x)], // other diagonal
-    ]
+    self
+      .relevant_sequence_indices(ptr)
+      .map(|index| &sequences[index])
+  }
+
+  /// Get indices of the sequences relevant for the given tile.
+  fn relevant_sequence_indices(&self, ptr: TilePointer) -> [usize; 4] {
+    SeqKind::ALL.map(|kind| self.sequence_index(SeqInfo::through(kind, ptr, self.width)))
+  }
+
+  fn sequence_index(&self, info: SeqInfo) -> usize {
+    info.position(self.width, self.height)
+  }
+
+  /// Get the number of stones of `player` on the board.
+  pub fn stone_count(&self, player: Player) -> usize {
+    self
+      .data
+      .iter()
+      .filter(|&&tile| tile == Some(player))
+      .count()
+  }
+
+  /// Get the number of empty tiles on the board.
+  pub fn empty_count(&self) -> usize {
+    self
+      .empty
+      .iter()
+      .map(|word| word.count_ones() as usize)
+      .sum()
+  }
+
+  /// Check if there are no empty tiles left.
+  pub fn is_full(&self) -> bool {
+    self.empty.iter().all(|&word| word == 0)
+  }
+
+  /// Get all tiles where `player` is allowed to play under the given rules.
+  ///
+  /// These are the empty tiles except the ones the rules forbid, in reading
+  /// order.
+  pub fn legal_moves(&self, player: Player, rules: &Rules) -> Vec<TilePointer> {
+    self.legal_moves_iter(player, rules).collect()
+  }
+
+  /// Same as [`Board::legal_moves`], without collecting the tiles.
+  pub(crate) fn legal_moves_iter<'a>(
+    &'a self,
+    player: Player,
+    rules: &'a Rules,
+  ) -> impl Iterator<Item = TilePointer> + 'a {
+    self
+      .pointers_to_empty_tiles()
+      .filter(move |&ptr| !rules.is_forbidden(self, ptr, player))
   }
 
-  /// Get iterator over all empty tiles in the board.
+  /// Get iterator over all empty tiles in the board, in reading order.
+  ///
+  /// The empty tiles are tracked on every change, so this doesn't scan the
+  /// whole board.
   pub fn pointers_to_empty_tiles(&self) -> impl Iterator<Item = TilePointer> + '_ {
+    self
+      .empty
+      .iter()
+      .enumerate()
+      .flat_map(|(word_index, &word)| {
+        // pop the lowest set bit until none is left
+        iter::successors(Some(word), |&word| Some(word & word.wrapping_sub(1)))
+          .take_while(|&word| word != 0)
+          .map(move |word| word_index * 64 + word.trailing_zeros() as usize)
+      })
+      .map(|index| self.get_ptr_from_index(index))
+  }
+
+  /// Get iterator over all stones on the board, row by row from the top left.
+  pub fn occupied_tiles(&self) -> impl Iterator<Item = (TilePointer, Player)> + '_ {
     self
       .data
       .iter()
       .enumerate()
-      .filter(|(.., tile)| tile.is_none())
-      .map(|(index, ..)| self.get_ptr_from_index(index))
+      .filter_map(|(index, tile)| tile.map(|player| (self.get_ptr_from_index(index), player)))
+  }
+
+  /// Get the tiles of the row `y`, from left to right.
+  ///
+  /// # Panics
+  /// Panics if `y` is out of bounds.
+  pub fn row(&self, y: u8) -> impl Iterator<Item = (TilePointer, &Tile)> + '_ {
+    assert!(y < self.height, "row {y} out of bounds");
+    self.sequence_tiles(self.sequence_index(SeqInfo {
+      kind: SeqKind::Row,
+      index: y.into(),
+    }))
+  }
+
+  /// Get the tiles of the column `x`, from top to bottom.
+  ///
+  /// # Panics
+  /// Panics if `x` is out of bounds.
+  pub fn column(&self, x: u8) -> impl Iterator<Item = (TilePointer, &Tile)> + '_ {
+    assert!(x < self.width, "column {x} out of bounds");
+    self.sequence_tiles(self.sequence_index(SeqInfo {
+      kind: SeqKind::Col,
+      index: x.into(),
+    }))
+  }
+
+  /// Get the tiles of a diagonal going down and to the right (`\`), from top
+  /// to bottom.
+  ///
+  /// The diagonals are indexed from the top right corner, so tile `(x, y)`
+  /// lies on the diagonal `width - 1 - x + y`. Index 0 is the top right
+  /// corner alone and `width + height - 2` the bottom left one.
+  ///
+  /// # Panics
+  /// Panics if `index` is out of bounds.
+  pub fn diagonal(&self, index: usize) -> impl Iterator<Item = (TilePointer, &Tile)> + '_ {
+    assert!(
+      index < self.diagonal_count(),
+      "diagonal {index} out of bounds"
+    );
+
+    self.sequence_tiles(self.sequence_index(SeqInfo {
+      kind: SeqKind::DiagLR,
+      index: index as u16,
+    }))
+  }
+
+  /// Get the tiles of a diagonal going down and to the left (`/`), from top
+  /// to bottom.
+  ///
+  /// The diagonals are indexed from the top left corner, so tile `(x, y)`
+  /// lies on the diagonal `x + y`. Index 0 is the top left corner alone and
+  /// `width + height - 2` the bottom right one.
+  ///
+  /// # Panics
+  /// Panics if `index` is out of bounds.
+  pub fn anti_diagonal(&self, index: usize) -> impl Iterator<Item = (TilePointer, &Tile)> + '_ {
+    assert!(
+      index < self.diagonal_count(),
+      "anti-diagonal {index} out of bounds"
+    );
+
+    self.sequence_tiles(self.sequence_index(SeqInfo {
+      kind: SeqKind::DiagRL,
+      index: index as u16,
+    }))
+  }
+
+  /// Get the number of diagonals in either direction.
+  pub fn diagonal_count(&self) -> usize {
+    usize::from(self.width) + usize::from(self.height) - 1
+  }
+
+  fn sequence_tiles(&self, index: usize) -> impl Iterator<Item = (TilePointer, &Tile)> + '_ {
+    self.sequences()[index]
+      .iter()
+      .map(|&index| (self.get_ptr_from_index(index), &self.data[index]))
   }
 
   /// Get reference to slice of all tiles in the board.
@@ -160,32 +684,56 @@ impl Board {
     &self.data
   }
 
+  /// Get a copy of the tiles as a 2D vector, row by row.
+  ///
+  /// Inverse of [`Board::new`].
+  pub fn to_grid(&self) -> Vec<Vec<Tile>> {
+    self
+      .data
+      .chunks(self.width as usize)
+      .map(<[Tile]>::to_vec)
+      .collect()
+  }
+
+  /// Get the center tile of the board.
+  ///
+  /// On even sizes there are two middle rows or columns, and the center
+  /// rounds towards the higher one, e.g. `(8, 8)` on a 16x16 board.
+  pub fn center(&self) -> TilePointer {
+    TilePointer {
+      x: self.width / 2,
+      y: self.height / 2,
+    }
+  }
+
   /// Calculate the square of the distance from the center of the board.
   pub fn squared_distance_from_center(&self, p: TilePointer) -> Score {
-    let center = f32::from(self.size - 1) / 2.0; // -1 to adjust for 0-indexing
+    // -1 to adjust for 0-indexing
+    let center_x = f32::from(self.width - 1) / 2.0;
+    let center_y = f32::from(self.height - 1) / 2.0;
 
     let x = f32::from(p.x);
     let y = f32::from(p.y);
-    let dist = (x - center).powi(2) + (y - center).powi(2);
+    let dist = (x - center_x).powi(2) + (y - center_y).powi(2);
 
     dist.round() as Score
   }
 
   /// Convert a raw index to `TilePointer`.
   pub fn get_ptr_from_index(&self, index: usize) -> TilePointer {
-    let x = (index % self.size as usize) as u8;
-    let y = (index / self.size as usize) as u8;
+    let x = (index % self.width as usize) as u8;
+    let y = (index / self.width as usize) as u8;
 
     TilePointer { x, y }
   }
 
-  fn get_index(size: u8, ptr: TilePointer) -> usize {
+  fn get_index(width: u8, ptr: TilePointer) -> usize {
     let TilePointer { x, y } = ptr;
-    Self::get_index_raw(size, x, y)
+    Self::get_index_raw(width, x, y)
   }
 
-  fn get_index_raw(size: u8, x: u8, y: u8) -> usize {
-    usize::from(size) * usize::from(y) + usize::from(x)
+  fn get_index_raw(width: u8, x: u8, y: u8) -> usize {
+    usize::from(width) * usize::from(y) + usize::from(x)
   }
 
   /// Get value of a tile at the given pointer.
@@ -193,10 +741,20 @@ impl Board {
   /// # Panics
   /// Panics if the pointer is out of bounds.
   pub fn get_tile(&self, ptr: TilePointer) -> &Tile {
-    let index = Self::get_index(self.size, ptr);
+    let index = Self::get_index(self.width, ptr);
     self.get_tile_raw(index)
   }
 
+  /// Get value of a tile at the given pointer, or `None` if the pointer is
+  /// out of bounds.
+  pub fn get(&self, ptr: TilePointer) -> Option<&Tile> {
+    if ptr.x >= self.width || ptr.y >= self.height {
+      return None;
+    }
+
+    self.data.get(Self::get_index(self.width, ptr))
+  }
+
   /// Get value of a tile at the given index.
   ///
   /// # Panics
@@ -208,14 +766,83 @@ impl Board {
       .unwrap_or_else(|| panic!("Tile index out of bounds: {index}"))
   }
 
+  /// Place a stone of `player` at the given pointer.
+  ///
+  /// Non-panicking alternative to [`Board::set_tile`] for playing moves.
+  ///
+  /// # Errors
+  /// Returns an error if the pointer is out of bounds or the tile is already
+  /// occupied.
+  pub fn place(&mut self, ptr: TilePointer, player: Player) -> Result<(), PlaceError> {
+    match self.get(ptr) {
+      None => Err(PlaceError::OutOfBounds(ptr)),
+      Some(Some(_)) => Err(PlaceError::Occupied(ptr)),
+      Some(None) => {
+        self.set_tile(ptr, Some(player));
+        Ok(())
+      },
+    }
+  }
+
   /// Set a tile at the given pointer.
   ///
   /// # Panics
   /// Panics at attempt to overwrite an already occupied tile.
   pub fn set_tile(&mut self, ptr: TilePointer, value: Tile) {
-    let index = Self::get_index(self.size, ptr);
+    self.write_tile(ptr, value);
+
+    for seq_index in self.relevant_sequence_indices(ptr) {
+      let eval = self.evaluate_sequence(&self.sequences()[seq_index], &Heuristic::DEFAULT);
+      self.evals.update(seq_index, eval);
+    }
+  }
+
+  /// Place a stone of `player` on the empty tile and return a token to take
+  /// it back with [`Board::undo`].
+  ///
+  /// Unlike [`Board::set_tile`] with `None`, the undo restores the cached
+  /// evaluation instead of computing it again.
+  ///
+  /// # Panics
+  /// Panics if the tile is occupied.
+  pub fn apply(&mut self, ptr: TilePointer, player: Player) -> Undo {
+    let evals = self
+      .relevant_sequence_indices(ptr)
+      .map(|index| (index, self.evals.get(index)));
+
+    let last_move = self.last_move;
+
+    self.set_tile(ptr, Some(player));
+
+    Undo {
+      ptr,
+      evals,
+      last_move,
+    }
+  }
+
+  /// Take back a move made by [`Board::apply`].
+  ///
+  /// Moves have to be taken back in the reverse order they were made in.
+  ///
+  /// # Panics
+  /// Panics if the tile of the move is empty.
+  #[allow(clippy::needless_pass_by_value)] // consumed so it can't be used twice
+  pub fn undo(&mut self, undo: Undo) {
+    self.write_tile(undo.ptr, None);
+    self.last_move = undo.last_move;
+
+    for (index, eval) in undo.evals {
+      self.evals.update(index, eval);
+    }
+  }
 
-    let tile = self.get_tile_raw(index);
+  /// Write the tile and update everything derived from it except the
+  /// evaluation.
+  fn write_tile(&mut self, ptr: TilePointer, value: Tile) {
+    let index = Self::get_index(self.width, ptr);
+
+    let tile = *self.get_tile_raw(index);
 
     // either write Some to empty tile (play) or None to occupied tile (undo)
     assert!(
@@ -224,106 +851,621 @@ impl Board {
     );
 
     self.data[index] = value;
+    self.hash ^= zobrist_key(index, value.or(tile).expect("checked above"));
+    self.empty[index / 64] ^= 1 << (index % 64);
+
+    #[cfg(feature = "bitboard")]
+    self
+      .bits
+      .toggle(ptr.x, ptr.y, value.or(tile).expect("checked above"));
+
+    if value.is_some() {
+      self.last_move = Some(ptr);
+    } else if self.last_move == Some(ptr) {
+      self.last_move = None;
+    }
   }
 
-  /// Get the size of the board.
-  pub fn size(&self) -> u8 {
-    self.size
+  /// Get the tile of the last stone placed, if known.
+  ///
+  /// Updated by every placement and cleared when that stone is removed with
+  /// [`Board::set_tile`], or restored to the previous one by [`Board::undo`].
+  /// Boards created from tiles, parsed or transformed don't know it. Purely
+  /// informational, e.g. for highlighting with [`Board::render_colored`], so
+  /// it doesn't affect equality or evaluation.
+  pub fn last_move(&self) -> Option<TilePointer> {
+    self.last_move
   }
 
-  fn evaluate_sequence(&self, sequence: &[usize]) -> Eval {
-    let mut eval = Eval::default();
+  /// Get the Zobrist hash of the board.
+  ///
+  /// Equal positions have equal hashes, so it can be used to detect repeated
+  /// positions. It is updated incrementally on each move and undo. Hashes of
+  /// boards with different dimensions aren't comparable.
+  pub fn zobrist(&self) -> u64 {
+    self.hash
+  }
 
-    let mut current = Player::X; // current player
-    let mut consecutive = 0; // consecutive tiles of the current player
-    let mut open_ends = 0; // open ends of consecutive tiles
-    let mut has_hole = false; // is there a hole in the consecutive tiles
+  /// Compute the Zobrist hash from scratch.
+  fn compute_zobrist(&self) -> u64 {
+    self
+      .data
+      .iter()
+      .enumerate()
+      .filter_map(|(index, tile)| tile.map(|player| zobrist_key(index, player)))
+      .fold(0, |hash, key| hash ^ key)
+  }
 
-    for (i, &tile_idx) in sequence.iter().enumerate() {
-      if let Some(player) = self.data[tile_idx] {
-        if player == current {
-          consecutive += 1;
-          continue;
-        }
+  /// Compute the bit set of empty tiles from scratch.
+  fn compute_empty(&self) -> Box<[u64]> {
+    let mut empty = vec![0; self.data.len().div_ceil(64)];
 
-        // opponent's tile
-        if consecutive > 0 {
-          let (shape_score, is_win_shape) = shape_score(consecutive, open_ends, has_hole);
-          eval.score[current] += shape_score;
-          eval.win[current] |= is_win_shape;
+    for (index, tile) in self.data.iter().enumerate() {
+      if tile.is_none() {
+        empty[index / 64] |= 1 << (index % 64);
+      }
+    }
 
-          open_ends = 0;
-          has_hole = false;
-        }
+    empty.into_boxed_slice()
+  }
 
-        consecutive = 1;
-        current = player;
-      } else {
-        // empty tile
-        if consecutive == 0 {
-          open_ends = 1; // If there were no consecutive tiles yet, mark as an open end
-          has_hole = false;
+  /// Get the size of a square board.
+  ///
+  /// For rectangular boards this is the width, use [`Board::width`] and
+  /// [`Board::height`] instead.
+  pub fn size(&self) -> u8 {
+    self.width
+  }
+
+  /// Get the number of columns.
+  pub fn width(&self) -> u8 {
+    self.width
+  }
+
+  /// Get the number of rows.
+  pub fn height(&self) -> u8 {
+    self.height
+  }
+
+  /// Create a new board with the tiles moved by the symmetry.
+  pub(crate) fn transform(&self, symmetry: Symmetry) -> Board {
+    let (width, height) = symmetry.dimensions(self.width, self.height);
+    let mut data = vec![None; self.data.len()].into_boxed_slice();
+
+    for (index, &tile) in self.data.iter().enumerate() {
+      let ptr = self.get_ptr_from_index(index);
+      let target = symmetry.apply(ptr, self.width, self.height);
+      data[Self::get_index(width, target)] = tile;
+    }
+
+    Board::from_flat(width, height, data)
+  }
+
+  /// Rotate the board by 90 degrees clockwise.
+  ///
+  /// Width and height of rectangular boards are swapped.
+  #[must_use]
+  pub fn rotate90(&self) -> Board {
+    self.transform(Symmetry::ROTATE_90)
+  }
+
+  /// Mirror the board along the vertical axis, swapping left and right.
+  #[must_use]
+  pub fn mirror_horizontal(&self) -> Board {
+    self.transform(Symmetry::MIRROR_HORIZONTAL)
+  }
+
+  /// Mirror the board along the horizontal axis, swapping top and bottom.
+  #[must_use]
+  pub fn mirror_vertical(&self) -> Board {
+    self.transform(Symmetry::MIRROR_VERTICAL)
+  }
+
+  /// Get all symmetric variants of the board, starting with the board itself.
+  ///
+  /// These are the 8 rotations and reflections for square boards, but only
+  /// the 4 that keep the shape for rectangular ones.
+  pub fn symmetries(&self) -> Vec<Board> {
+    Symmetry::all(self.width, self.height)
+      .map(|symmetry| self.transform(symmetry))
+      .collect()
+  }
+
+  /// Check if `other` is the same board up to a rotation or reflection.
+  ///
+  /// Unlike `==`, which compares the tiles as they are, this is true for
+  /// any of the 8 rotations and reflections of the board, including the
+  /// ones swapping width and height of rectangular boards.
+  pub fn eq_symmetric(&self, other: &Board) -> bool {
+    Symmetry::dihedral()
+      .filter(|symmetry| {
+        symmetry.dimensions(self.width, self.height) == (other.width, other.height)
+      })
+      .any(|symmetry| {
+        self.data.iter().enumerate().all(|(index, &tile)| {
+          let target = symmetry.apply(self.get_ptr_from_index(index), self.width, self.height);
+          other.data[Self::get_index(other.width, target)] == tile
+        })
+      })
+  }
+
+  /// Get the symmetries, other than the identity, that map the board onto
+  /// itself.
+  pub(crate) fn self_symmetries(&self) -> Vec<Symmetry> {
+    Symmetry::all(self.width, self.height)
+      .filter(|&symmetry| symmetry != Symmetry::IDENTITY && self.transform(symmetry) == *self)
+      .collect()
+  }
+
+  /// Check if the tile is the first one in reading order among the tiles it
+  /// is mapped to by the symmetries.
+  pub(crate) fn is_first_symmetric(&self, ptr: TilePointer, symmetries: &[Symmetry]) -> bool {
+    symmetries.iter().all(|symmetry| {
+      let other = symmetry.apply(ptr, self.width, self.height);
+      (other.y, other.x) >= (ptr.y, ptr.x)
+    })
+  }
+
+  /// Get the lexicographically smallest of the [symmetric
+  /// variants](Board::symmetries), so all of them share one representative.
+  ///
+  /// Tiles are compared in reading order, with empty < X < O.
+  #[must_use]
+  pub fn canonical(&self) -> Board {
+    self.transform(self.canonical_symmetry())
+  }
+
+  /// Get the symmetry transforming the board into its
+  /// [canonical](Board::canonical) form.
+  pub(crate) fn canonical_symmetry(&self) -> Symmetry {
+    let key = |tile: Tile| match tile {
+      None => 0_u8,
+      Some(Player::X) => 1,
+      Some(Player::O) => 2,
+    };
+
+    // compare the keys in reading order of the transformed board, without
+    // building it
+    let keys = |symmetry: Symmetry| {
+      let (width, _) = symmetry.dimensions(self.width, self.height);
+      let mut keys = vec![0; self.data.len()];
+
+      for (index, &tile) in self.data.iter().enumerate() {
+        let target = symmetry.apply(self.get_ptr_from_index(index), self.width, self.height);
+        keys[Self::get_index(width, target)] = key(tile);
+      }
+
+      keys
+    };
+
+    Symmetry::all(self.width, self.height)
+      .min_by_key(|&symmetry| keys(symmetry))
+      .unwrap_or(Symmetry::IDENTITY)
+  }
+
+  /// Check if the player has five or more in a row using the bitboard.
+  #[cfg(feature = "bitboard")]
+  pub(crate) fn has_five(&self, player: Player) -> bool {
+    self.bits.has_five(player)
+  }
+
+  /// Find a completed five on the board.
+  ///
+  /// Returns the player and the five tiles forming the line. Under freestyle
+  /// rules an overline (six or more) also wins, in which case the first five
+  /// tiles of the line are returned. Same as [`Board::winner_with`] with the
+  /// default rules.
+  pub fn winner(&self) -> Option<(Player, [TilePointer; 5])> {
+    self.winner_with(&Rules::default())
+  }
+
+  /// Find a five that wins under the given rules.
+  ///
+  /// Same as [`Board::winner`], but overlines count only for players the
+  /// rules allow them to, so under Renju a black (X) overline doesn't win.
+  pub fn winner_with(&self, rules: &Rules) -> Option<(Player, [TilePointer; 5])> {
+    self.sequences().iter().find_map(|sequence| {
+      let is_player = |index: Option<&usize>, player: Player| {
+        index.is_some_and(|&idx| self.data[idx] == Some(player))
+      };
+
+      sequence.windows(5).enumerate().find_map(|(start, window)| {
+        let player = self.data[window[0]]?;
+
+        if !window.iter().all(|&idx| self.data[idx] == Some(player)) {
+          return None;
+        }
+
+        let overline = (start > 0 && is_player(sequence.get(start - 1), player))
+          || is_player(sequence.get(start + 5), player);
+
+        if overline && !rules.allows_overline(player) {
+          return None;
+        }
+
+        Some((
+          player,
+          std::array::from_fn(|i| self.get_ptr_from_index(window[i])),
+        ))
+      })
+    })
+  }
+
+  fn evaluate_sequence(&self, sequence: &[usize], heuristic: &Heuristic) -> Eval {
+    Self::evaluate_sequence_by(sequence, heuristic, |index| self.data[index])
+  }
+
+  /// Evaluate the sequence with tiles given by `tile_at`, so the board can be
+  /// evaluated as if it had a different tile somewhere.
+  fn evaluate_sequence_by(
+    sequence: &[usize],
+    heuristic: &Heuristic,
+    tile_at: impl Fn(usize) -> Tile,
+  ) -> Eval {
+    Self::evaluate_window_by(sequence, 0, sequence.len(), heuristic, tile_at)
+  }
+
+  /// Evaluate only the shapes of the sequence whose first stone lies at a
+  /// position in `from..to`.
+  ///
+  /// Shapes are still scanned past both ends of the window, so they score the
+  /// same as in the whole sequence, and evaluations of disjoint windows add up
+  /// to the evaluation of the sequence.
+  fn evaluate_window_by(
+    sequence: &[usize],
+    from: usize,
+    to: usize,
+    heuristic: &Heuristic,
+    tile_at: impl Fn(usize) -> Tile,
+  ) -> Eval {
+    let mut eval = Eval::default();
+    let max_holes = if heuristic.two_holes { 2 } else { 1 };
+
+    let is_empty = |i: usize| tile_at(sequence[i]).is_none();
+
+    // two empty tiles in a row always end any shape, so the scan can start
+    // right after the last such pair before the window
+    let scan_from = (2..=from)
+      .rev()
+      .find(|&i| is_empty(i - 2) && is_empty(i - 1))
+      .map_or(0, |i| i - 1);
+
+    let mut current = Player::X; // current player
+    let mut consecutive = 0; // consecutive tiles of the current player
+    let mut open_ends = 0; // open ends of consecutive tiles
+    let mut holes = 0; // number of holes in the consecutive tiles
+    let mut after_hole = 0; // consecutive tiles after the last hole
+    let mut start = 0; // position of the first stone of the current shape
+
+    for (i, &tile_idx) in sequence.iter().enumerate().skip(scan_from) {
+      // no shape in progress, so all the following ones start past the window
+      if i >= to && consecutive == 0 {
+        break;
+      }
+
+      let counts = (from..to).contains(&start);
+
+      if let Some(player) = tile_at(tile_idx) {
+        if player == current {
+          if consecutive == 0 {
+            start = i;
+          }
+
+          consecutive += 1;
+
+          if holes > 0 {
+            after_hole += 1;
+
+            // five after the hole wins on its own, so drop the tiles before
+            // the hole and keep the hole as an open end
+            if after_hole == 5 {
+              consecutive = 5;
+              open_ends = 1;
+              holes = 0;
+              start = i - 4;
+            }
+          }
+
+          continue;
+        }
+
+        // opponent's tile
+        if consecutive > 0 {
+          if counts {
+            let (shape_score, is_win_shape) =
+              heuristic.holed_shape_score(consecutive, open_ends, holes);
+            eval.score[current] += i64::from(shape_score);
+            eval.win[current] |= is_win_shape;
+          }
+
+          open_ends = 0;
+          holes = 0;
+        }
+
+        consecutive = 1;
+        current = player;
+        start = i;
+      } else {
+        // empty tile
+        if consecutive == 0 {
+          open_ends = 1; // If there were no consecutive tiles yet, mark as an open end
+          holes = 0;
           continue;
         }
 
-        // If there is no hole yet, and the next tile is of the current player,
+        // If there are holes left, and the next tile is of the current player,
         // and consecutive count is less than 5, mark as a hole
-        if !has_hole
+        if holes < max_holes
           && consecutive < 5
-          && sequence.get(i + 1).and_then(|&idx| self.data[idx]) == Some(current)
+          && sequence.get(i + 1).and_then(|&idx| tile_at(idx)) == Some(current)
         {
-          has_hole = true;
+          holes += 1;
+          after_hole = 0;
           consecutive += 1;
           continue;
         }
 
         open_ends += 1;
 
-        let (shape_score, is_win_shape) = shape_score(consecutive, open_ends, has_hole);
-        eval.score[current] += shape_score;
-        eval.win[current] |= is_win_shape;
+        if counts {
+          let (shape_score, is_win_shape) =
+            heuristic.holed_shape_score(consecutive, open_ends, holes);
+          eval.score[current] += i64::from(shape_score);
+          eval.win[current] |= is_win_shape;
+        }
 
         consecutive = 0;
         open_ends = 1;
-        has_hole = false;
+        holes = 0;
       }
     }
 
     // If there are consecutive tiles at the end of the sequence
-    if consecutive > 0 {
-      let (shape_score, is_win_shape) = shape_score(consecutive, open_ends, has_hole);
-      eval.score[current] += shape_score;
+    if consecutive > 0 && (from..to).contains(&start) {
+      let (shape_score, is_win_shape) = heuristic.holed_shape_score(consecutive, open_ends, holes);
+      eval.score[current] += i64::from(shape_score);
       eval.win[current] |= is_win_shape;
     }
 
     eval
   }
 
+  /// Evaluate the part of a sequence within `radius` positions of `center`
+  ///
+  /// Positions are indices into `sequence`, not board indices. Only shapes
+  /// starting inside the window count, but they are followed past its edges
+  /// and score as in the whole sequence. So windows that don't overlap never
+  /// count a shape twice, and windows covering the sequence add up to its
+  /// full evaluation.
+  pub fn evaluate_window(&self, sequence: &[usize], center: usize, radius: usize) -> Eval {
+    self.evaluate_window_with(sequence, center, radius, &Heuristic::DEFAULT)
+  }
+
+  /// Evaluate the part of a sequence within `radius` positions of `center`
+  /// using custom shape scores
+  ///
+  /// See [`Board::evaluate_window`].
+  pub fn evaluate_window_with(
+    &self,
+    sequence: &[usize],
+    center: usize,
+    radius: usize,
+    heuristic: &Heuristic,
+  ) -> Eval {
+    let from = center.saturating_sub(radius).min(sequence.len());
+    let to = center
+      .saturating_add(radius)
+      .saturating_add(1)
+      .min(sequence.len());
+
+    Self::evaluate_window_by(sequence, from, to, heuristic, |index| self.data[index])
+  }
+
   /// Evaluate sequences relevat to given tile
   ///
   /// Relevant means the column, row and both diagonals that include the tile.
   pub fn evaluate_sequences_relevant_to(&self, tile: TilePointer) -> Eval {
-    self
-      .relevant_sequences(tile)
+    self.evaluate_sequences_relevant_to_with(tile, &Heuristic::DEFAULT)
+  }
+
+  /// Evaluate sequences relevat to given tile using custom shape scores
+  ///
+  /// Relevant means the column, row and both diagonals that include the tile.
+  pub fn evaluate_sequences_relevant_to_with(
+    &self,
+    tile: TilePointer,
+    heuristic: &Heuristic,
+  ) -> Eval {
+    let indices = self.relevant_sequence_indices(tile).into_iter();
+
+    if heuristic.has_default_shapes() {
+      indices.map(|index| self.evals.get(index)).sum()
+    } else {
+      indices
+        .map(|index| self.evaluate_sequence(&self.sequences()[index], heuristic))
+        .sum()
+    }
+  }
+
+  /// Score the change of the position for `player` placing a stone at the
+  /// empty `tile`, without any parent score.
+  ///
+  /// Also returns the evaluation of the relevant sequences after the move.
+  /// The board is left unchanged.
+  pub(crate) fn move_gain(
+    &mut self,
+    tile: TilePointer,
+    player: Player,
+    heuristic: &Heuristic,
+  ) -> (Score, Eval) {
+    let opponent = !player;
+
+    let Eval {
+      score: prev_score, ..
+    } = self.evaluate_sequences_relevant_to_with(tile, heuristic);
+
+    let undo = self.apply(tile, player);
+    let new_eval = self.evaluate_sequences_relevant_to_with(tile, heuristic);
+    self.undo(undo);
+
+    let mut gain = new_eval.score[player] - new_eval.score[opponent] - prev_score[player]
+      + prev_score[opponent]
+      - i64::from(heuristic.center_bias) * i64::from(self.squared_distance_from_center(tile));
+
+    if heuristic.double_threat != 0 && self.double_threat(tile, player).is_some() {
+      gain += i64::from(heuristic.double_threat);
+    }
+
+    (clamp_score(gain), new_eval)
+  }
+
+  /// Evaluate the board for `player` as if they placed a stone at the empty
+  /// `ptr`, without changing the board.
+  ///
+  /// Same as placing the stone, calling [`Board::evaluate_for`] and removing
+  /// it, but only the sequences through `ptr` are evaluated.
+  ///
+  /// # Panics
+  /// Panics if the tile is occupied.
+  pub fn score_move(&self, ptr: TilePointer, player: Player) -> (Score, State) {
+    self.score_move_with(ptr, player, &Heuristic::DEFAULT)
+  }
+
+  /// Same as [`Board::score_move`], but using custom shape scores.
+  ///
+  /// # Panics
+  /// Panics if the tile is occupied.
+  pub fn score_move_with(
+    &self,
+    ptr: TilePointer,
+    player: Player,
+    heuristic: &Heuristic,
+  ) -> (Score, State) {
+    let placed = Self::get_index(self.width, ptr);
+    assert!(self.data[placed].is_none(), "tile {ptr} is occupied");
+
+    let tile_at = |index| {
+      if index == placed {
+        Some(player)
+      } else {
+        self.data[index]
+      }
+    };
+
+    let before = self.evaluate_sequences_relevant_to_with(ptr, heuristic);
+    let after = self
+      .relevant_sequences(ptr)
       .into_iter()
-      .map(|seq| self.evaluate_sequence(seq))
-      .sum()
+      .map(|sequence| Self::evaluate_sequence_by(sequence, heuristic, tile_at))
+      .sum::<Eval>();
+
+    let total = self.evaluate_with(heuristic);
+    let score = total.score - before.score + after.score;
+
+    let state = if total.win[player] || after.win[player] {
+      State::Win
+    } else {
+      State::NotEnd
+    };
+
+    (clamp_score(score[player] - score[!player]), state)
+  }
+
+  /// Get an iterator over every empty tile scored by [`Board::score_move`]
+  /// for `player`.
+  ///
+  /// The moves are evaluated lazily in board order, without sorting or
+  /// dropping any, so callers can rank and filter them as they need. The
+  /// board isn't touched by the evaluation.
+  pub fn scored_moves(&self, player: Player) -> impl Iterator<Item = Move> + '_ {
+    self.pointers_to_empty_tiles().map(move |tile| {
+      let (score, _) = self.score_move(tile, player);
+      Move { tile, score }
+    })
+  }
+
+  /// Get the shallow score of playing every empty tile
+  ///
+  /// These are the scores the engine starts with when considering the moves,
+  /// useful e.g. for rendering an overlay of promising moves.
+  pub fn heatmap(&self, player: Player) -> Vec<(TilePointer, Score)> {
+    self.heatmap_with(player, &Heuristic::DEFAULT)
+  }
+
+  /// Get the shallow score of playing every empty tile using custom shape
+  /// scores
+  pub fn heatmap_with(&self, player: Player, heuristic: &Heuristic) -> Vec<(TilePointer, Score)> {
+    let (initial_score, _) = self.evaluate_for_with(!player, heuristic);
+    let mut board = self.clone();
+
+    self
+      .pointers_to_empty_tiles()
+      .map(|tile| {
+        let (gain, _) = board.move_gain(tile, player, heuristic);
+        (tile, gain - initial_score)
+      })
+      .collect()
   }
 
   /// Evaluate the whole board and return summary for both players
+  ///
+  /// The evaluation is cached and updated on every [`Board::set_tile`], so
+  /// this is cheap.
   pub fn evaluate(&self) -> Eval {
+    self.evals.total()
+  }
+
+  /// Evaluate the whole board using custom shape scores and return summary
+  /// for both players
+  ///
+  /// Only the default [`Heuristic`] is cached, other ones evaluate the board
+  /// from scratch.
+  pub fn evaluate_with(&self, heuristic: &Heuristic) -> Eval {
+    if heuristic.has_default_shapes() {
+      self.evaluate()
+    } else {
+      self.evaluate_uncached(heuristic)
+    }
+  }
+
+  /// Evaluate the whole board from scratch, ignoring the cache.
+  pub(crate) fn evaluate_uncached(&self, heuristic: &Heuristic) -> Eval {
     self
       .sequences()
       .iter()
-      .map(|seq| self.evaluate_sequence(seq))
+      .map(|seq| self.evaluate_sequence(seq, heuristic))
       .sum()
   }
 
+  /// Get the raw shape scores of both players as `(X, O)`.
+  ///
+  /// Each is the sum of the scores of the player's shapes, without
+  /// subtracting anything for the opponent, clamped to
+  /// [`MAX_EVAL`](crate::MAX_EVAL).
+  pub fn scores(&self) -> (Score, Score) {
+    let score = self.evaluate().score;
+
+    (clamp_score(score[Player::X]), clamp_score(score[Player::O]))
+  }
+
   /// Evaluate the whole board and return result for target player
+  ///
+  /// The score is from the perspective of `target`, i.e. its shape score
+  /// minus the opponent's one, see [`Board::scores`]. Positive means `target`
+  /// is better off, clamped to [`MAX_EVAL`](crate::MAX_EVAL). The state is
+  /// [`State::Win`] if `target` has a five, otherwise [`State::NotEnd`].
   pub fn evaluate_for(&self, target: Player) -> (Score, State) {
-    let Eval { score, win } = self.evaluate();
+    self.evaluate_for_with(target, &Heuristic::DEFAULT)
+  }
+
+  /// Evaluate the whole board using custom shape scores and return result for
+  /// target player
+  ///
+  /// Same perspective as [`Board::evaluate_for`].
+  pub fn evaluate_for_with(&self, target: Player, heuristic: &Heuristic) -> (Score, State) {
+    let Eval { score, win } = self.evaluate_with(heuristic);
 
-    let score = score[target] - score[!target];
+    let score = clamp_score(score[target] - score[!target]);
 
     let state = if win[target] {
       State::Win
@@ -335,15 +1477,103 @@ impl Board {
   }
 }
 
+/// Serialized form of [`Board`]
+///
+/// Deserialization goes through [`Board::new`], so the shape is re-validated.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RawBoard {
+  width: u8,
+  height: u8,
+  tiles: Vec<Tile>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Board> for RawBoard {
+  fn from(board: Board) -> Self {
+    RawBoard {
+      width: board.width,
+      height: board.height,
+      tiles: board.data.into_vec(),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawBoard> for Board {
+  type Error = Error;
+
+  fn try_from(raw: RawBoard) -> Result<Self, Self::Error> {
+    let smaller = raw.width.min(raw.height);
+    if smaller < Board::MIN_SIZE {
+      return Err(Error::TooSmall {
+        size: smaller as usize,
+      });
+    }
+
+    let mut rows = raw
+      .tiles
+      .chunks(raw.width as usize)
+      .map(<[Tile]>::to_vec)
+      .collect::<Vec<_>>();
+
+    if rows.len() != raw.height as usize {
+      return Err(Error::NotRectangular {
+        expected: raw.width as usize,
+        line: rows.len(),
+        width: rows.pop().map_or(0, |row| row.len()),
+      });
+    }
+
+    Board::new(rows)
+  }
+}
+
+impl Default for Board {
+  fn default() -> Self {
+    Board::new_empty(Board::DEFAULT_SIZE).expect("the default size is valid")
+  }
+}
+
+impl PartialEq for Board {
+  fn eq(&self, other: &Self) -> bool {
+    // evaluations and sequences are derived from the rest
+    self.width == other.width && self.height == other.height && self.data == other.data
+  }
+}
+impl Eq for Board {}
+
+impl fmt::Debug for Board {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Board")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("data", &self.data)
+      .field("evals", &self.evals)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Index<TilePointer> for Board {
+  type Output = Tile;
+
+  /// Same as [`Board::get_tile`].
+  fn index(&self, ptr: TilePointer) -> &Tile {
+    self.get_tile(ptr)
+  }
+}
+
 impl FromStr for Board {
   type Err = Error;
 
   /// Parse a string into a board.
   ///
   /// Expects the same format, that is produced by [`Board::to_string`].
+  /// Any character other than `'x'` and `'o'` is read as an empty tile, use
+  /// [`Board::from_str_strict`] to reject them instead.
   ///
   /// # Errors
-  /// Returns an error if the board is not a square or is too small.
+  /// Returns an error if the board is not a rectangle or is too small.
   fn from_str(input_string: &str) -> Result<Board, Self::Err> {
     // split string into Vec<Vec<chars>>
     let rows = input_string
@@ -371,26 +1601,125 @@ impl FromStr for Board {
     Ok(board)
   }
 }
+impl TryFrom<&str> for Board {
+  type Error = Error;
 
-impl fmt::Display for Board {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    let board_size = self.size as usize;
+  /// Same as [`Board::from_str`].
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    Board::from_str(value)
+  }
+}
+impl TryFrom<Vec<Vec<Tile>>> for Board {
+  type Error = Error;
+
+  /// Same as [`Board::new`].
+  fn try_from(value: Vec<Vec<Tile>>) -> Result<Self, Self::Error> {
+    Board::new(value)
+  }
+}
 
-    let indent = if board_size >= 10 { " " } else { "" };
+impl Board {
+  /// Render the board using Unicode stones, `●` for X and `○` for O, on a
+  /// dotted grid with coordinate headers.
+  ///
+  /// Meant for display only, the output can't be parsed back. Use
+  /// [`Display`](fmt::Display) for that.
+  #[allow(clippy::missing_panics_doc)] // writing to a String can't fail
+  pub fn render_unicode(&self) -> String {
+    let mut output = String::new();
 
-    writeln!(
-      f,
-      "{indent} {}",
-      &"abcdefghijklmnopqrstuvwxyz"[..board_size]
-    )?;
+    self
+      .write_grid(&mut output, " ", |_, tile| match tile {
+        Some(Player::X) => '\u{25cf}',
+        Some(Player::O) => '\u{25cb}',
+        None => '\u{00b7}',
+      })
+      .expect("writing to a String can't fail");
 
-    for (i, row) in self.data.chunks(board_size).enumerate() {
-      write!(f, "{}{}", if i + 1 < 10 { indent } else { "" }, i + 1)?;
+    output
+  }
 
-      row
-        .iter()
-        .map(|field| field.map_or('-', Player::char))
-        .try_for_each(|c| write!(f, "{c}"))?;
+  /// Render the board like [`Display`](fmt::Display), with the stones of
+  /// each player colored using ANSI escape codes and the last move
+  /// highlighted.
+  ///
+  /// Without the `color` feature the plain [`Display`](fmt::Display) output
+  /// is returned.
+  #[allow(clippy::missing_panics_doc)] // writing to a String can't fail
+  pub fn render_colored(&self, last_move: Option<TilePointer>) -> String {
+    #[cfg(feature = "color")]
+    {
+      const RESET: &str = "\x1b[0m";
+
+      let mut output = String::new();
+
+      self
+        .write_grid(&mut output, "", |ptr, tile| {
+          let c = tile.map_or('-', Player::char);
+          let color = match tile {
+            Some(Player::X) => "\x1b[31m",
+            Some(Player::O) => "\x1b[34m",
+            None => return c.to_string(),
+          };
+          // the last move is shown inverted
+          let highlight = if Some(ptr) == last_move {
+            "\x1b[7m"
+          } else {
+            ""
+          };
+
+          format!("{color}{highlight}{c}{RESET}")
+        })
+        .expect("writing to a String can't fail");
+
+      output
+    }
+
+    #[cfg(not(feature = "color"))]
+    {
+      let _ = last_move;
+      self.to_string()
+    }
+  }
+
+  /// Write the rows with row numbers and column names above them, the
+  /// columns separated by `separator`.
+  fn write_grid<D: fmt::Display>(
+    &self,
+    f: &mut impl fmt::Write,
+    separator: &str,
+    tile_str: impl Fn(TilePointer, Tile) -> D,
+  ) -> fmt::Result {
+    let width = self.height.to_string().len();
+
+    let names = (0..self.width)
+      .map(TilePointer::column_name)
+      .collect::<Vec<_>>();
+    let header_lines = names.last().map_or(1, String::len);
+
+    // multi-letter column names are written vertically to keep the columns
+    // one character wide
+    for line in 0..header_lines {
+      write!(f, "{:width$}", "")?;
+
+      for name in &names {
+        let padding = header_lines - name.len();
+        let c = line
+          .checked_sub(padding)
+          .map_or(' ', |i| name.as_bytes()[i] as char);
+        write!(f, "{separator}{c}")?;
+      }
+
+      writeln!(f)?;
+    }
+
+    for (y, row) in (0..self.height).zip(self.data.chunks(self.width as usize)) {
+      write!(f, "{:>width$}", u16::from(y) + 1)?;
+
+      (0..self.width)
+        .zip(row)
+        .map(|(x, &tile)| tile_str(TilePointer { x, y }, tile))
+        .try_for_each(|c| write!(f, "{separator}{c}"))?;
 
       writeln!(f)?;
     }
@@ -398,10 +1727,16 @@ impl fmt::Display for Board {
     Ok(())
   }
 }
+impl fmt::Display for Board {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.write_grid(f, "", |_, tile| tile.map_or('-', Player::char))
+  }
+}
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::MAX_EVAL;
 
   const BOARD_DATA: &str = "---------
 ---------
@@ -422,52 +1757,1116 @@ mod tests {
   }
 
   #[test]
-  fn test_initialize_sequences() {
-    let board_size = BOARD_SIZE;
+  fn test_render_unicode() {
+    let mut board = Board::new_rect(9, 10).unwrap();
+    board.set_tile(TilePointer { x: 0, y: 0 }, Some(Player::X));
+    board.set_tile(TilePointer { x: 8, y: 9 }, Some(Player::O));
+
+    let rendered = board.render_unicode();
+    let lines = rendered.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], "   a b c d e f g h i");
+    assert_eq!(
+      lines[1],
+      " 1 \u{25cf} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7}"
+    );
+    assert_eq!(
+      lines[10],
+      "10 \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{b7} \u{25cb}"
+    );
+  }
 
-    let board = Board::new_empty(board_size);
+  #[test]
+  fn test_render_colored() {
+    let mut board = Board::new_rect(9, 10).unwrap();
+    board.set_tile(TilePointer { x: 0, y: 0 }, Some(Player::X));
+    board.set_tile(TilePointer { x: 8, y: 9 }, Some(Player::O));
 
-    assert!(!board.sequences().is_empty());
+    let rendered = board.render_colored(Some(TilePointer { x: 8, y: 9 }));
 
-    let mut visits = vec![0; board_size.pow(2) as usize];
+    #[cfg(not(feature = "color"))]
+    assert_eq!(rendered, board.to_string());
 
-    for sequence in board.sequences() {
-      for index in sequence {
-        visits[*index] += 1;
-      }
-    }
+    #[cfg(feature = "color")]
+    {
+      let lines = rendered.lines().collect::<Vec<_>>();
 
-    for visit in &visits {
-      assert_eq!(*visit, 4);
+      assert_eq!(lines[1], " 1\x1b[31mx\x1b[0m--------");
+      assert_eq!(lines[10], "10--------\x1b[34m\x1b[7mo\x1b[0m");
     }
   }
 
   #[test]
-  fn test_get_index() {
-    let x = 2;
-    let y = 3;
-    let tile = TilePointer { x, y };
-    let target = (x + y * BOARD_SIZE) as usize;
+  fn test_stone_count() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
 
-    assert_eq!(Board::get_index_raw(BOARD_SIZE, x, y), target);
-    assert_eq!(Board::get_index(BOARD_SIZE, tile), target);
+    assert_eq!(board.stone_count(Player::X), 7);
+    assert_eq!(board.stone_count(Player::O), 6);
+    assert_eq!(board.empty_count(), 81 - 13);
+    assert!(!board.is_full());
+
+    let full = Board::from_str(&"xxooxxoox\nooxxooxxo\n".repeat(5)).unwrap();
+    assert!(full.is_full());
+    assert_eq!(full.empty_count(), 0);
+    assert_eq!(full.stone_count(Player::O), 90 / 2);
   }
 
   #[test]
-  fn test_get_relevant_sequences() {
-    let board = Board::from_str(BOARD_DATA).unwrap();
+  fn test_zobrist() {
+    let mut board = Board::from_str(BOARD_DATA).unwrap();
+    let initial = board.zobrist();
 
-    for x in 0..BOARD_SIZE {
-      for y in 0..BOARD_SIZE {
-        let tile = TilePointer { x, y };
-        let target = Board::get_index(BOARD_SIZE, tile);
+    assert_eq!(initial, board.compute_zobrist());
+    assert_ne!(initial, Board::new_empty(9).unwrap().zobrist());
 
-        let sequences = board.relevant_sequences(tile);
+    let moves = [(1, 1, Player::X), (7, 2, Player::O), (2, 8, Player::X)];
 
-        sequences
-          .iter()
-          .for_each(|sequence| assert!(sequence.iter().any(|index| *index == target)));
+    for (x, y, player) in moves {
+      board.set_tile(TilePointer { x, y }, Some(player));
+      assert_eq!(board.zobrist(), board.compute_zobrist());
+    }
+
+    let played = board.zobrist();
+    assert_ne!(played, initial);
+
+    for (x, y, _) in moves.into_iter().rev() {
+      board.set_tile(TilePointer { x, y }, None);
+      assert_eq!(board.zobrist(), board.compute_zobrist());
+    }
+
+    assert_eq!(board.zobrist(), initial);
+
+    // same stones in a different order give the same hash
+    for (x, y, player) in moves.into_iter().rev() {
+      board.set_tile(TilePointer { x, y }, Some(player));
+    }
+
+    assert_eq!(board.zobrist(), played);
+  }
+
+  #[test]
+  fn test_occupied_tiles() {
+    let board = Board::from_str(
+      "---------
+----o----
+---------
+--x---x--
+---------
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    assert_eq!(
+      board.occupied_tiles().collect::<Vec<_>>(),
+      vec![
+        (TilePointer { x: 4, y: 1 }, Player::O),
+        (TilePointer { x: 2, y: 3 }, Player::X),
+        (TilePointer { x: 6, y: 3 }, Player::X),
+      ]
+    );
+    assert_eq!(
+      board.occupied_tiles().count() + board.pointers_to_empty_tiles().count(),
+      board.tiles().len()
+    );
+  }
+
+  #[test]
+  fn test_empty_tiles_tracking() {
+    // more than one word of the bit set
+    let mut board = Board::new_rect(13, 11).unwrap();
+    let mut played = Vec::new();
+
+    let scan = |board: &Board| {
+      board
+        .tiles()
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| tile.is_none())
+        .map(|(index, _)| board.get_ptr_from_index(index))
+        .collect::<Vec<_>>()
+    };
+
+    for step in 0..2000 {
+      let random = splitmix64(step) as usize;
+      let next = board.pointers_to_empty_tiles().nth(random / 3 % 64);
+
+      if random.is_multiple_of(3) && !played.is_empty() {
+        let ptr = played.swap_remove(random / 3 % played.len());
+        board.set_tile(ptr, None);
+      } else if let Some(ptr) = next {
+        board.set_tile(ptr, Some(Player::all()[played.len() % 2]));
+        played.push(ptr);
+      }
+
+      let empty = scan(&board);
+      assert_eq!(board.pointers_to_empty_tiles().collect::<Vec<_>>(), empty);
+      assert_eq!(board.empty_count(), empty.len());
+      assert_eq!(board.is_full(), empty.is_empty());
+    }
+  }
+
+  #[test]
+  fn test_scores_perspective() {
+    // X has an open three, O a lone stone
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+--xxx----
+---------
+---------
+---------
+--------o",
+    )
+    .unwrap();
+
+    let (x, o) = board.scores();
+    assert!(x > o);
+
+    assert_eq!(board.evaluate_for(Player::X), (x - o, State::NotEnd));
+    assert_eq!(board.evaluate_for(Player::O), (o - x, State::NotEnd));
+  }
+
+  #[test]
+  fn test_apply_undo() {
+    let initial = Board::from_str(BOARD_DATA).unwrap();
+    let mut board = initial.clone();
+    let mut stack = Vec::new();
+
+    let assert_consistent = |board: &Board| {
+      let fresh = Board::new(board.to_grid()).unwrap();
+
+      assert_eq!(board.evaluate(), fresh.evaluate());
+      assert_eq!(board.zobrist(), fresh.zobrist());
+      assert_eq!(board.empty_count(), fresh.empty_count());
+    };
+
+    for step in 0..500 {
+      let random = splitmix64(step) as usize;
+      let empty = board.empty_count();
+
+      // grow the stack more often than shrink it, and unwind it at the end
+      if (random % 5 < 2 || empty == 0) && !stack.is_empty() {
+        let undo: Undo = stack.pop().unwrap();
+        let tile = undo.tile();
+
+        board.undo(undo);
+        assert_eq!(*board.get_tile(tile), None);
+      } else {
+        let tile = board
+          .pointers_to_empty_tiles()
+          .nth(random / 5 % empty)
+          .unwrap();
+        stack.push(board.apply(tile, Player::all()[random / 7 % 2]));
+      }
+
+      assert_consistent(&board);
+    }
+
+    while let Some(undo) = stack.pop() {
+      board.undo(undo);
+    }
+
+    assert_eq!(board, initial);
+    assert_eq!(board.evaluate(), initial.evaluate());
+    assert_eq!(board.zobrist(), initial.zobrist());
+  }
+
+  #[test]
+  fn test_lines() {
+    let board = Board::from_str(&"-x-------\n".repeat(10)).unwrap();
+
+    let pointers = |line: Vec<(TilePointer, &Tile)>| {
+      line
+        .into_iter()
+        .map(|(ptr, _)| (ptr.x, ptr.y))
+        .collect::<Vec<_>>()
+    };
+
+    let row = board.row(2).collect::<Vec<_>>();
+    assert_eq!(row.len(), 9);
+    assert_eq!(row[1], (TilePointer { x: 1, y: 2 }, &Some(Player::X)));
+
+    let column = board.column(1).collect::<Vec<_>>();
+    assert_eq!(column.len(), 10);
+    assert!(column.iter().all(|(_, tile)| **tile == Some(Player::X)));
+
+    assert_eq!(board.diagonal_count(), 18);
+    assert_eq!(pointers(board.diagonal(0).collect()), [(8, 0)]);
+    assert_eq!(pointers(board.diagonal(8).collect())[..2], [(0, 0), (1, 1)]);
+    assert_eq!(pointers(board.diagonal(17).collect()), [(0, 9)]);
+    assert_eq!(pointers(board.anti_diagonal(0).collect()), [(0, 0)]);
+    assert_eq!(
+      pointers(board.anti_diagonal(2).collect()),
+      [(2, 0), (1, 1), (0, 2)]
+    );
+    assert_eq!(pointers(board.anti_diagonal(17).collect()), [(8, 9)]);
+
+    // every tile lies on the documented diagonals
+    for index in 0..board.diagonal_count() {
+      for (ptr, _) in board.diagonal(index) {
+        assert_eq!(8 - usize::from(ptr.x) + usize::from(ptr.y), index);
+      }
+      for (ptr, _) in board.anti_diagonal(index) {
+        assert_eq!(usize::from(ptr.x + ptr.y), index);
+      }
+    }
+  }
+
+  #[test]
+  fn test_score_move() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+    let aggressive = Heuristic {
+      open_three: 50_000,
+      ..Heuristic::DEFAULT
+    };
+
+    for ptr in board.pointers_to_empty_tiles() {
+      for player in Player::all() {
+        let mut placed = board.clone();
+        placed.set_tile(ptr, Some(player));
+
+        assert_eq!(board.score_move(ptr, player), placed.evaluate_for(player));
+        assert_eq!(
+          board.score_move_with(ptr, player, &aggressive),
+          placed.evaluate_for_with(player, &aggressive)
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_dense_board_does_not_overflow() {
+    // fives and fours of X in every row, thousands of times the score of a
+    // single five in total
+    let mut board = Board::new_empty(60).unwrap();
+    for y in 0..60 {
+      for x in (0..60).filter(|x| x % 6 != 5) {
+        board.set_tile(TilePointer { x, y }, Some(Player::X));
       }
     }
+
+    assert_eq!(board.evaluate_for(Player::X), (MAX_EVAL, State::Win));
+    assert_eq!(board.evaluate_for(Player::O).0, -MAX_EVAL);
+    assert_eq!(board.scores(), (MAX_EVAL, 0));
+
+    let gap = TilePointer { x: 5, y: 0 };
+    assert!(board.score_move(gap, Player::O).0.abs() <= MAX_EVAL);
+
+    let huge = Heuristic {
+      five: Score::MAX,
+      ..Heuristic::DEFAULT
+    };
+    assert_eq!(board.evaluate_for_with(Player::X, &huge).0, MAX_EVAL);
+    assert!(board.score_move_with(gap, Player::X, &huge).0 <= MAX_EVAL);
+  }
+
+  #[test]
+  fn test_split_shapes() {
+    let heuristic = Heuristic {
+      split_four: 9_000,
+      open_split_three: 700,
+      split_three: 50,
+      ..Heuristic::DEFAULT
+    };
+
+    let score = |row: &str, heuristic: &Heuristic| {
+      let tiles = row
+        .chars()
+        .map(|c| Player::from_char(c).ok())
+        .collect::<Vec<_>>();
+      let sequence = (0..tiles.len()).collect::<Vec<_>>();
+
+      Board::evaluate_sequence_by(&sequence, heuristic, |index| tiles[index]).score[Player::O]
+    };
+
+    assert_eq!(score("--o-oo--", &heuristic), 700);
+    assert_eq!(score("-xo-oo--", &heuristic), 50);
+    assert_eq!(score("--oo-ox-", &heuristic), 50);
+    assert_eq!(score("-oo-oo--", &heuristic), 9_000);
+    assert_eq!(score("xo-ooo-x", &heuristic), 9_000);
+
+    // the second hole ends the shape unless enabled
+    assert_eq!(score("--o-o-o--", &heuristic), 0);
+
+    let two_holes = Heuristic {
+      two_holes: true,
+      ..heuristic
+    };
+    assert_eq!(score("--o-o-o--", &two_holes), 700);
+    assert_eq!(score("-oo-o-o--", &two_holes), 700);
+    assert_eq!(score("-oo-oo--", &two_holes), 9_000);
+  }
+
+  #[test]
+  fn test_evaluate_window() {
+    let two_holes = Heuristic {
+      two_holes: true,
+      ..Heuristic::DEFAULT
+    };
+
+    for seed in 0..8 {
+      let mut board = Board::new_empty(15).unwrap();
+
+      for i in 0..120 {
+        let tile = board.get_ptr_from_index((splitmix64(seed * 1000 + i) % 225) as usize);
+        if board.get_tile(tile).is_none() {
+          let player = if i % 2 == 0 { Player::X } else { Player::O };
+          board.set_tile(tile, Some(player));
+        }
+      }
+
+      for heuristic in [&Heuristic::DEFAULT, &two_holes] {
+        for sequence in board.sequences() {
+          let full = board.evaluate_sequence(sequence, heuristic);
+
+          for radius in 0..=5 {
+            let size = 2 * radius + 1;
+            let windows = (radius..sequence.len() + size)
+              .step_by(size)
+              .map(|center| board.evaluate_window_with(sequence, center, radius, heuristic))
+              .sum::<Eval>();
+
+            assert_eq!(windows, full, "seed {seed}, radius {radius}");
+          }
+        }
+      }
+    }
+
+    // a shape crossing the window edge scores fully, but only in the window
+    // with its first stone
+    let board = Board::from_str(
+      "---------
+-oo-o--x-
+---------
+---------
+---------
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+    let row = &board.sequences()[1];
+    let full = board.evaluate_sequence(row, &Heuristic::DEFAULT);
+
+    assert_ne!(full, Eval::default());
+    assert_eq!(board.evaluate_window(row, 1, 1), full);
+    assert_eq!(board.evaluate_window(row, 4, 2), Eval::default());
+
+    let x_only = board.evaluate_window(row, 7, 0);
+    assert_eq!(x_only.score[Player::X], full.score[Player::X]);
+    assert_eq!(x_only.score[Player::O], 0);
+  }
+
+  #[test]
+  fn test_shapes_at_edge() {
+    // evaluate a board with `row` on top and the rest empty, so only the row
+    // scores
+    let evaluate = |row: &str| {
+      let empty = format!("{}\n", "-".repeat(row.len()));
+      Board::from_str(&format!("{row}\n{}", empty.repeat(8)))
+        .unwrap()
+        .evaluate()
+    };
+
+    // the edge blocks a shape just like an opponent's stone
+    for row in [
+      "---ooo-o", "--ooo-oo", "---oo-oo", "----oo-o", "---o-ooo", "----oooo", "o-ooo---",
+    ] {
+      let at_edge = evaluate(&format!("-{row}"));
+      let blocked = evaluate(&format!("-{row}x"));
+
+      assert_eq!(at_edge.score[Player::O], blocked.score[Player::O], "{row}");
+    }
+
+    // a split four scores the same at either edge
+    assert_eq!(evaluate("---ooo-o-"), evaluate("-o-ooo---"));
+    assert_eq!(evaluate("----ooo-o"), evaluate("o-ooo----"));
+
+    // five after a hole still wins
+    assert!(evaluate("--o-ooooo").win[Player::O]);
+    assert!(evaluate("ooooo-o--").win[Player::O]);
+    assert!(!evaluate("--o-oooo-").win[Player::O]);
+  }
+
+  #[test]
+  fn test_legal_moves() {
+    let board = Board::from_str(
+      "---------
+---------
+----x----
+----x----
+--xx-----
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let double_three = TilePointer { x: 4, y: 4 };
+
+    let freestyle = board.legal_moves(Player::X, &Rules::freestyle());
+    assert_eq!(freestyle.len(), 81 - 4);
+    assert!(freestyle.contains(&double_three));
+
+    let black = board.legal_moves(Player::X, &Rules::renju());
+    assert_eq!(black.len(), 81 - 4 - 1);
+    assert!(!black.contains(&double_three));
+
+    let white = board.legal_moves(Player::O, &Rules::renju());
+    assert!(white.contains(&double_three));
+  }
+
+  #[test]
+  fn test_winner() {
+    let mut board = Board::from_str(BOARD_DATA).unwrap();
+
+    assert_eq!(board.winner(), None);
+
+    board.set_tile(TilePointer { x: 3, y: 4 }, Some(Player::X));
+    board.set_tile(TilePointer { x: 3, y: 6 }, Some(Player::X));
+
+    let expected = [2, 3, 4, 5, 6].map(|y| TilePointer { x: 3, y });
+
+    assert_eq!(board.winner(), Some((Player::X, expected)));
+    assert_eq!(
+      board.winner_with(&Rules::renju()),
+      Some((Player::X, expected))
+    );
+  }
+
+  #[test]
+  fn test_winner_with_overline() {
+    let mut board = Board::new_empty(BOARD_SIZE).unwrap();
+    let row = |y| (1..7).map(move |x| TilePointer { x, y });
+
+    for tile in row(2) {
+      board.set_tile(tile, Some(Player::X));
+    }
+
+    let expected = [1, 2, 3, 4, 5].map(|x| TilePointer { x, y: 2 });
+    assert_eq!(board.winner(), Some((Player::X, expected)));
+    assert_eq!(board.winner_with(&Rules::renju()), None);
+
+    // white wins with an overline even under Renju
+    for tile in row(6) {
+      board.set_tile(tile, Some(Player::O));
+    }
+
+    let expected = [1, 2, 3, 4, 5].map(|x| TilePointer { x, y: 6 });
+    assert_eq!(
+      board.winner_with(&Rules::renju()),
+      Some((Player::O, expected))
+    );
+  }
+
+  #[test]
+  fn test_grid_round_trip() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+
+    let grid = board.to_grid();
+
+    assert_eq!(grid.len(), BOARD_SIZE as usize);
+    assert_eq!(grid[2][3], Some(Player::X));
+    assert_eq!(grid[3][4], Some(Player::O));
+    assert_eq!(Board::new(grid).unwrap(), board);
+
+    let chars = BOARD_DATA
+      .lines()
+      .map(|row| row.chars().collect())
+      .collect();
+
+    assert_eq!(Board::try_from_chars(chars).unwrap(), board);
+  }
+
+  #[test]
+  fn test_try_from_chars_rejects_invalid() {
+    let mut chars: Vec<Vec<char>> = BOARD_DATA
+      .lines()
+      .map(|row| row.chars().collect())
+      .collect();
+    chars[1][4] = '?';
+
+    assert!(matches!(
+      Board::try_from_chars(chars),
+      Err(Error::InvalidTile {
+        line: 2,
+        column: 5,
+        value: '?'
+      })
+    ));
+  }
+
+  #[test]
+  fn test_from_str_strict() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+    assert_eq!(Board::from_str_strict(BOARD_DATA).unwrap(), board);
+
+    let dotted = BOARD_DATA.replace('-', ".");
+    assert_eq!(Board::from_str_strict(&dotted).unwrap(), board);
+
+    let spaced = BOARD_DATA.replacen('-', " ", 3);
+    assert_eq!(Board::from_str_strict(&spaced).unwrap(), board);
+
+    let labeled = BOARD_DATA
+      .lines()
+      .enumerate()
+      .map(|(y, row)| format!("{}{row}", char::from(b'a' + y as u8)))
+      .collect::<Vec<_>>()
+      .join("\n");
+    assert!(Board::from_str(&labeled).is_ok());
+    assert_eq!(
+      Board::from_str_strict(&labeled),
+      Err(Error::InvalidTile {
+        line: 1,
+        column: 1,
+        value: 'a'
+      })
+    );
+  }
+
+  #[test]
+  fn test_last_move() {
+    let mut board = Board::new_empty(9).unwrap();
+    assert_eq!(board.last_move(), None);
+
+    let first = TilePointer { x: 4, y: 4 };
+    let second = TilePointer { x: 5, y: 4 };
+
+    board.place(first, Player::X).unwrap();
+    assert_eq!(board.last_move(), Some(first));
+
+    let undo = board.apply(second, Player::O);
+    assert_eq!(board.last_move(), Some(second));
+    assert_eq!(board.clone().last_move(), Some(second));
+
+    let mut other = Board::new_empty(9).unwrap();
+    other.set_tile(second, Some(Player::O));
+    other.set_tile(first, Some(Player::X));
+    assert_eq!(other, board);
+
+    board.undo(undo);
+    assert_eq!(board.last_move(), Some(first));
+
+    board.set_tile(first, None);
+    assert_eq!(board.last_move(), None);
+  }
+
+  #[test]
+  fn test_scored_moves() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+    let before = board.clone();
+
+    let moves = board.scored_moves(Player::O).collect::<Vec<_>>();
+
+    assert_eq!(moves.len(), board.empty_count());
+    assert_eq!(board, before);
+    assert_eq!(board.evaluate(), before.evaluate());
+
+    for move_ in moves {
+      assert_eq!(move_.score, board.score_move(move_.tile, Player::O).0);
+    }
+  }
+
+  #[test]
+  fn test_try_from() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+
+    assert_eq!(Board::try_from(BOARD_DATA).unwrap(), board);
+    assert_eq!(Board::try_from(board.to_grid()).unwrap(), board);
+    assert_eq!(Board::try_from("xo\nox"), Err(Error::TooSmall { size: 2 }));
+    assert_eq!(
+      Board::try_from(vec![vec![None; 9]; 8]),
+      Board::new(vec![vec![None; 9]; 8])
+    );
+  }
+
+  #[test]
+  fn test_get_and_place() {
+    let mut board = Board::from_str(BOARD_DATA).unwrap();
+
+    let ptr = TilePointer { x: 3, y: 2 };
+    let outside = TilePointer {
+      x: BOARD_SIZE,
+      y: 0,
+    };
+    let empty = TilePointer { x: 0, y: 0 };
+
+    assert_eq!(board[ptr], Some(Player::X));
+    assert_eq!(board.get(ptr), Some(&Some(Player::X)));
+    assert_eq!(board.get(outside), None);
+
+    let before = board.clone();
+
+    assert_eq!(board.place(ptr, Player::O), Err(PlaceError::Occupied(ptr)));
+    assert_eq!(
+      board.place(outside, Player::O),
+      Err(PlaceError::OutOfBounds(outside))
+    );
+    assert_eq!(board, before);
+
+    assert_eq!(board.place(empty, Player::O), Ok(()));
+    assert_eq!(board[empty], Some(Player::O));
+  }
+
+  #[test]
+  fn test_replay() {
+    let ptr = |x, y| TilePointer { x, y };
+    let moves = [
+      (Player::X, ptr(4, 4)),
+      (Player::O, ptr(5, 4)),
+      (Player::X, ptr(4, 5)),
+    ];
+
+    let board = Board::replay(BOARD_SIZE, &moves).unwrap();
+
+    assert_eq!(board[ptr(4, 4)], Some(Player::X));
+    assert_eq!(board[ptr(5, 4)], Some(Player::O));
+    assert_eq!(board[ptr(4, 5)], Some(Player::X));
+
+    let occupied = [(Player::X, ptr(4, 4)), (Player::O, ptr(4, 4))];
+
+    assert_eq!(
+      Board::replay(BOARD_SIZE, &occupied),
+      Err(ReplayError::Illegal {
+        ply: 1,
+        error: PlaceError::Occupied(ptr(4, 4))
+      })
+    );
+
+    let twice = [(Player::X, ptr(4, 4)), (Player::X, ptr(4, 5))];
+
+    assert_eq!(
+      Board::replay(BOARD_SIZE, &twice),
+      Err(ReplayError::WrongPlayer {
+        ply: 1,
+        expected: Player::O
+      })
+    );
+    assert!(Board::replay_with(BOARD_SIZE, &twice, false).is_ok());
+
+    let mut finished = (0..5).map(|x| (Player::X, ptr(x, 0))).collect::<Vec<_>>();
+    finished.push((Player::O, ptr(0, 1)));
+
+    assert_eq!(
+      Board::replay_with(BOARD_SIZE, &finished, false),
+      Err(ReplayError::GameEnded { ply: 5 })
+    );
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde_round_trip() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+
+    let json = serde_json::to_string(&board).unwrap();
+    let deserialized: Board = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(board, deserialized);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde_rejects_misshaped() {
+    let json = r#"{"width":9,"height":9,"tiles":[null,null,null]}"#;
+    let error = serde_json::from_str::<Board>(json).unwrap_err();
+    assert!(error.to_string().contains("not a rectangle"));
+
+    let json = format!(
+      r#"{{"width":3,"height":3,"tiles":[{}]}}"#,
+      ["null"; 9].join(",")
+    );
+    let error = serde_json::from_str::<Board>(&json).unwrap_err();
+    assert!(error.to_string().contains("too small"));
+  }
+
+  #[test]
+  fn test_incremental_evaluation() {
+    let mut board = Board::new_empty(BOARD_SIZE).unwrap();
+    let mut played = Vec::new();
+
+    // simple LCG, so the test is deterministic
+    let mut seed = 0x2545_F491_4F6C_DD1D_u64;
+    let mut random = move |max: usize| {
+      seed = seed
+        .wrapping_mul(6_364_136_223_846_793_005)
+        .wrapping_add(1_442_695_040_888_963_407);
+      (seed >> 33) as usize % max
+    };
+
+    for i in 0..500 {
+      if played.is_empty() || random(3) > 0 {
+        let empty = board.pointers_to_empty_tiles().collect::<Vec<_>>();
+        let Some(&tile) = empty.get(random(empty.len().max(1))) else {
+          continue;
+        };
+        let player = if i % 2 == 0 { Player::X } else { Player::O };
+
+        board.set_tile(tile, Some(player));
+        played.push(tile);
+      } else {
+        let tile = played.swap_remove(random(played.len()));
+        board.set_tile(tile, None);
+      }
+
+      let rows = board
+        .tiles()
+        .chunks(BOARD_SIZE as usize)
+        .map(<[Tile]>::to_vec)
+        .collect();
+      let fresh = Board::new(rows).unwrap();
+
+      assert_eq!(board.evaluate(), fresh.evaluate());
+      assert_eq!(board, fresh);
+    }
+  }
+
+  #[test]
+  fn test_initialize_sequences() {
+    let board_size = BOARD_SIZE;
+
+    let board = Board::new_empty(board_size).unwrap();
+
+    assert!(!board.sequences().is_empty());
+
+    let mut visits = vec![0; board_size.pow(2) as usize];
+
+    for sequence in board.sequences() {
+      for index in sequence {
+        visits[*index] += 1;
+      }
+    }
+
+    for visit in &visits {
+      assert_eq!(*visit, 4);
+    }
+  }
+
+  #[test]
+  fn test_tile_pointer_columns() {
+    let cases = [(0, "a1"), (25, "z1"), (26, "aa1"), (51, "az1"), (52, "ba1")];
+
+    for (x, name) in cases {
+      let tile = TilePointer { x, y: 0 };
+
+      assert_eq!(tile.to_string(), name);
+      assert_eq!(TilePointer::try_from(name).unwrap(), tile);
+    }
+
+    for x in 0..=u8::MAX {
+      let tile = TilePointer { x, y: u8::MAX };
+      assert_eq!(
+        TilePointer::try_from(tile.to_string().as_str()).unwrap(),
+        tile
+      );
+    }
+
+    for y in 0..=u8::MAX {
+      for x in 0..=u8::MAX {
+        let tile = TilePointer { x, y };
+        assert_eq!(tile.to_string().parse::<TilePointer>().unwrap(), tile);
+      }
+    }
+
+    assert_eq!(
+      "h8".parse::<TilePointer>().unwrap(),
+      TilePointer { x: 7, y: 7 }
+    );
+
+    assert!(TilePointer::try_from("a0").is_err());
+    assert!(TilePointer::try_from("1").is_err());
+    assert!(TilePointer::try_from("zzz1").is_err());
+  }
+
+  #[test]
+  fn test_tile_pointer_neighbors() {
+    let ptr = |x, y| TilePointer { x, y };
+
+    assert_eq!(
+      ptr(3, 3).offset(-2, 1, BOARD_SIZE, BOARD_SIZE),
+      Some(ptr(1, 4))
+    );
+    assert_eq!(ptr(0, 0).offset(-1, 0, BOARD_SIZE, BOARD_SIZE), None);
+    assert_eq!(ptr(8, 8).offset(0, 1, BOARD_SIZE, BOARD_SIZE), None);
+    assert_eq!(ptr(8, 8).offset(0, 1, 9, 12), Some(ptr(8, 9)));
+    assert_eq!(ptr(8, 8).offset(1, 0, 9, 12), None);
+
+    let corner = ptr(0, 0)
+      .neighbors(BOARD_SIZE, BOARD_SIZE)
+      .collect::<Vec<_>>();
+    assert_eq!(corner, [ptr(1, 0), ptr(0, 1), ptr(1, 1)]);
+
+    let corner = ptr(8, 8)
+      .neighbors(BOARD_SIZE, BOARD_SIZE)
+      .collect::<Vec<_>>();
+    assert_eq!(corner, [ptr(7, 7), ptr(8, 7), ptr(7, 8)]);
+
+    assert_eq!(ptr(0, 4).neighbors(BOARD_SIZE, BOARD_SIZE).count(), 5);
+    assert_eq!(ptr(8, 4).neighbors(12, 9).count(), 8);
+    assert_eq!(ptr(4, 4).neighbors(BOARD_SIZE, BOARD_SIZE).count(), 8);
+  }
+
+  #[test]
+  fn test_get_index() {
+    let x = 2;
+    let y = 3;
+    let tile = TilePointer { x, y };
+    let target = (x + y * BOARD_SIZE) as usize;
+
+    assert_eq!(Board::get_index_raw(BOARD_SIZE, x, y), target);
+    assert_eq!(Board::get_index(BOARD_SIZE, tile), target);
+  }
+
+  #[test]
+  fn test_get_relevant_sequences() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+
+    for x in 0..BOARD_SIZE {
+      for y in 0..BOARD_SIZE {
+        let tile = TilePointer { x, y };
+        let target = Board::get_index(BOARD_SIZE, tile);
+
+        let sequences = board.relevant_sequences(tile);
+
+        for sequence in sequences {
+          assert!(sequence.contains(&target));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_sequence_kinds() {
+    let board = Board::new_rect(12, 9).unwrap();
+
+    for ptr in board.pointers_to_empty_tiles() {
+      let target = Board::get_index(board.width(), ptr);
+
+      for kind in SeqKind::ALL {
+        let mut containing = board
+          .sequences_with_info()
+          .filter(|(info, sequence)| info.kind == kind && sequence.contains(&target));
+
+        let (info, _) = containing.next().unwrap();
+        assert!(containing.next().is_none());
+        assert_eq!(info, SeqInfo::through(kind, ptr, board.width()));
+      }
+
+      for (kind, sequence) in SeqKind::ALL.into_iter().zip(board.relevant_sequences(ptr)) {
+        let index = board
+          .sequences()
+          .iter()
+          .position(|other| std::ptr::eq(other, sequence))
+          .unwrap();
+        assert_eq!(board.sequence_info(index).kind, kind);
+      }
+    }
+  }
+
+  #[test]
+  fn test_center() {
+    let odd = Board::new_empty(15).unwrap();
+    assert_eq!(odd.center(), TilePointer { x: 7, y: 7 });
+    assert_eq!(odd.squared_distance_from_center(odd.center()), 0);
+
+    let even = Board::new_empty(16).unwrap();
+    assert_eq!(even.center(), TilePointer { x: 8, y: 8 });
+
+    let rect = Board::new_rect(12, 9).unwrap();
+    assert_eq!(rect.center(), TilePointer { x: 6, y: 4 });
+  }
+
+  #[test]
+  fn test_relevant_sequences_contain_tile() {
+    for size in 9..=19 {
+      let board = Board::new_empty(size).unwrap();
+
+      for ptr in board.pointers_to_empty_tiles() {
+        let target = Board::get_index(size, ptr);
+
+        for sequence in board.relevant_sequences(ptr) {
+          assert!(sequence.contains(&target), "size {size}, tile {ptr}");
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_rectangular_board() {
+    let (width, height) = (12, 9);
+    let mut board = Board::new_rect(width, height).unwrap();
+
+    assert_eq!((board.width(), board.height()), (width, height));
+    assert_eq!(board.sequences().len(), 3 * (12 + 9) - 2);
+
+    for x in 0..width {
+      for y in 0..height {
+        let tile = TilePointer { x, y };
+        let target = Board::get_index(width, tile);
+
+        for sequence in board.relevant_sequences(tile) {
+          assert!(sequence.contains(&target));
+        }
+      }
+    }
+
+    let corner = TilePointer { x: 11, y: 8 };
+    assert_eq!(board.get(corner), Some(&None));
+    assert_eq!(board.get(TilePointer { x: 11, y: 9 }), None);
+
+    for y in 4..9 {
+      board.set_tile(TilePointer { x: 11, y }, Some(Player::O));
+    }
+
+    assert_eq!(board.winner().map(|(player, _)| player), Some(Player::O));
+
+    let rebuilt = Board::new(board.to_grid()).unwrap();
+    assert_eq!(rebuilt, board);
+    assert_eq!(rebuilt.evaluate(), board.evaluate());
+
+    let text = board
+      .to_grid()
+      .iter()
+      .map(|row| {
+        row
+          .iter()
+          .map(|tile| tile.map_or('-', Player::char))
+          .collect::<String>()
+      })
+      .collect::<Vec<_>>()
+      .join("\n");
+    assert_eq!(Board::from_str(&text).unwrap(), board);
+
+    assert!(matches!(
+      Board::from_str("----------\n---------"),
+      Err(Error::TooSmall { size: 2 })
+    ));
+  }
+
+  #[test]
+  fn test_small_boards() {
+    assert!(matches!(
+      Board::new(vec![vec![None; 3]; 3]),
+      Err(Error::TooSmall { size: 3 })
+    ));
+    assert!(matches!(
+      Board::new_unchecked_size(Vec::new()),
+      Err(Error::TooSmall { size: 0 })
+    ));
+
+    let tiny = Board::new_unchecked_size(vec![vec![None; 3]; 3]).unwrap();
+    assert_eq!(tiny.sequences().len(), 3 * (3 + 3) - 2);
+
+    for tile in tiny.pointers_to_empty_tiles() {
+      let target = Board::get_index(3, tile);
+
+      for sequence in tiny.relevant_sequences(tile) {
+        assert!(sequence.contains(&target));
+      }
+    }
+
+    let mut small = Board::new_unchecked_size(vec![vec![None; 5]; 5]).unwrap();
+    for i in 0..5 {
+      small.set_tile(TilePointer { x: i, y: i }, Some(Player::X));
+    }
+
+    assert_eq!(small.winner().map(|(player, _)| player), Some(Player::X));
+    assert_eq!(small.evaluate_for(Player::X).1, State::Win);
+  }
+
+  #[test]
+  fn test_too_large_boards() {
+    assert!(Board::new(vec![vec![None; 255]; 9]).is_ok());
+    assert_eq!(
+      Board::new(vec![vec![None; 256]; 9]),
+      Err(Error::TooLarge { size: 256 })
+    );
+    assert_eq!(
+      Board::new(vec![vec![None; 9]; 256]),
+      Err(Error::TooLarge { size: 256 })
+    );
+    assert_eq!(
+      Board::new(vec![vec![None; 300]; 300]),
+      Err(Error::TooLarge { size: 300 })
+    );
+  }
+
+  #[test]
+  fn test_default_board() {
+    let board = Board::default();
+    assert_eq!(board.size(), 15);
+    assert_eq!(board, Board::new_empty(15).unwrap());
+
+    assert_eq!(Board::new_empty(8), Err(Error::TooSmall { size: 8 }));
+    assert_eq!(Board::new_rect(12, 4), Err(Error::TooSmall { size: 4 }));
+    assert!(matches!(
+      Board::replay(5, &[]),
+      Err(ReplayError::InvalidBoard(Error::TooSmall { size: 5 }))
+    ));
+  }
+
+  #[test]
+  fn test_symmetries() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+
+    let rotated = board.rotate90();
+    assert_ne!(rotated, board);
+    assert_eq!(rotated[TilePointer { x: 6, y: 3 }], Some(Player::X));
+    assert_eq!(rotated.rotate90().rotate90().rotate90(), board);
+
+    assert_eq!(board.mirror_horizontal().mirror_horizontal(), board);
+    assert_eq!(board.mirror_vertical().mirror_vertical(), board);
+    assert_eq!(
+      board.rotate90().rotate90(),
+      board.mirror_horizontal().mirror_vertical()
+    );
+
+    let symmetries = board.symmetries();
+    assert_eq!(symmetries.len(), 8);
+
+    let canonical = board.canonical();
+    for variant in &symmetries {
+      assert!(symmetries.contains(&variant.canonical()));
+      assert_eq!(variant.canonical(), canonical);
+    }
+
+    let mut rect = Board::new_rect(12, 9).unwrap();
+    rect.set_tile(TilePointer { x: 0, y: 0 }, Some(Player::X));
+
+    let rotated = rect.rotate90();
+    assert_eq!((rotated.width(), rotated.height()), (9, 12));
+    assert_eq!(rotated[TilePointer { x: 8, y: 0 }], Some(Player::X));
+    assert_eq!(rect.symmetries().len(), 4);
+  }
+
+  #[test]
+  fn test_eq_symmetric() {
+    let board = Board::from_str(BOARD_DATA).unwrap();
+
+    let rotated = board.rotate90();
+    assert_ne!(rotated, board);
+    assert!(board.eq_symmetric(&rotated));
+    assert!(rotated.eq_symmetric(&board));
+
+    for variant in board.symmetries() {
+      assert!(variant.eq_symmetric(&board));
+    }
+
+    let mut other = board.clone();
+    other.set_tile(TilePointer { x: 0, y: 0 }, Some(Player::O));
+    assert!(!board.eq_symmetric(&other));
+    assert!(!board.eq_symmetric(&Board::new_empty(10).unwrap()));
+
+    let mut rect = Board::new_rect(12, 9).unwrap();
+    rect.set_tile(TilePointer { x: 0, y: 0 }, Some(Player::X));
+    assert!(rect.eq_symmetric(&rect.rotate90()));
+    assert!(!rect.eq_symmetric(&Board::new_rect(9, 12).unwrap()));
   }
 }
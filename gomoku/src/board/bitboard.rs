@@ -0,0 +1,68 @@
+use crate::Player;
+
+/// Stones of both players as bit sets over the tiles
+///
+/// Every row is padded with an always empty column, so lines running off the
+/// edge of a row don't continue on the next one. A line is then found by
+/// shifting the stones along its direction and intersecting the results.
+#[derive(Clone, Debug)]
+pub(crate) struct Bitboard {
+  /// Distance between the starts of two rows, i.e. the width plus padding
+  stride: usize,
+  /// Stones of each player, indexed by [`Player::as_index`]
+  stones: [Box<[u64]>; 2],
+}
+impl Bitboard {
+  /// Create an empty bitboard of the given dimensions.
+  pub fn new(width: u8, height: u8) -> Self {
+    let stride = usize::from(width) + 1;
+    let words = (stride * usize::from(height)).div_ceil(64);
+
+    Bitboard {
+      stride,
+      stones: [vec![0; words].into(), vec![0; words].into()],
+    }
+  }
+
+  /// Place or remove the stone of `player` at the tile.
+  pub fn toggle(&mut self, x: u8, y: u8, player: Player) {
+    let bit = usize::from(y) * self.stride + usize::from(x);
+
+    self.stones[player.as_index()][bit / 64] ^= 1 << (bit % 64);
+  }
+
+  /// Check if the player has five or more stones in a row.
+  pub fn has_five(&self, player: Player) -> bool {
+    let stones = &self.stones[player.as_index()];
+
+    // right, down, down-right and down-left
+    [1, self.stride, self.stride + 1, self.stride - 1]
+      .into_iter()
+      .any(|shift| {
+        // keep the stones followed by four more in the direction
+        let mut line = stones.to_vec();
+
+        for step in 1..5 {
+          and_shifted(&mut line, stones, step * shift);
+        }
+
+        line.iter().any(|&word| word != 0)
+      })
+  }
+}
+
+/// Intersect `target` with `bits` shifted towards the lower indices by `shift`.
+fn and_shifted(target: &mut [u64], bits: &[u64], shift: usize) {
+  let (words, offset) = (shift / 64, shift % 64);
+
+  for (index, word) in target.iter_mut().enumerate() {
+    let low = bits.get(index + words).copied().unwrap_or(0);
+    let high = bits.get(index + words + 1).copied().unwrap_or(0);
+
+    *word &= if offset == 0 {
+      low
+    } else {
+      (low >> offset) | (high << (64 - offset))
+    };
+  }
+}
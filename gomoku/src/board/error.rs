@@ -1,32 +1,148 @@
 use std::{error, fmt};
 
-#[derive(Debug, Clone)]
+use super::{Board, TilePointer};
+use crate::Player;
+
+/// Error returned when a board can't be created
+///
+/// Exported as [`BoardError`](crate::BoardError). Lines and columns are
+/// counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
+  /// A side of the board is shorter than allowed
   TooSmall {
+    /// Length of the shorter side, 0 for an empty board
+    size: usize,
+  },
+  /// A side of the board is longer than [`u8::MAX`]
+  TooLarge {
+    /// Length of the longer side
     size: usize,
   },
-  NotSquare {
-    height: usize,
+  /// The rows of the board don't have the same length
+  NotRectangular {
+    /// Length of the first row
+    expected: usize,
+    /// Line of the first row with a different length
     line: usize,
+    /// Length of that row
     width: usize,
   },
+  /// A character doesn't stand for any tile
+  InvalidTile {
+    /// Line of the character
+    line: usize,
+    /// Column of the character
+    column: usize,
+    /// The character itself
+    value: char,
+  },
 }
 
 impl fmt::Display for Error {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
-      Error::TooSmall { size } => write!(f, "board too small: {size}, but minimum is 9"),
-      Error::NotSquare {
-        height,
+      Error::TooSmall { size } => {
+        write!(
+          f,
+          "board too small: {size}, but minimum is {}",
+          Board::MIN_SIZE
+        )
+      },
+      Error::TooLarge { size } => {
+        write!(f, "board too large: {size}, but maximum is {}", u8::MAX)
+      },
+      Error::NotRectangular {
+        expected,
         line,
         width,
       } => {
         write!(
           f,
-          "board is not a square: line {line} is {width} tiles wide, but {height} was expected"
+          "board is not a rectangle: line {line} is {width} tiles wide, but {expected} was \
+           expected"
+        )
+      },
+      Error::InvalidTile {
+        line,
+        column,
+        value,
+      } => {
+        write!(
+          f,
+          "invalid tile '{value}' at line {line}, column {column}, expected 'x', 'o' or an empty tile"
         )
       },
     }
   }
 }
 impl error::Error for Error {}
+
+/// Error returned when a stone can't be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum PlaceError {
+  /// The tile is outside of the board
+  OutOfBounds(TilePointer),
+  /// The tile is already occupied
+  Occupied(TilePointer),
+  /// The rules don't allow the player to play at the tile
+  Forbidden(TilePointer),
+  /// The game has already ended
+  GameOver,
+}
+
+impl fmt::Display for PlaceError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PlaceError::OutOfBounds(ptr) => write!(f, "tile {ptr} is outside of the board"),
+      PlaceError::Occupied(ptr) => write!(f, "tile {ptr} is already occupied"),
+      PlaceError::Forbidden(ptr) => write!(f, "tile {ptr} is forbidden by the rules"),
+      PlaceError::GameOver => write!(f, "the game has already ended"),
+    }
+  }
+}
+impl error::Error for PlaceError {}
+
+/// Error returned when a game can't be replayed
+///
+/// `ply` is the zero-based index of the offending move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ReplayError {
+  /// The move can't be placed
+  Illegal {
+    /// Index of the move
+    ply: usize,
+    /// Why the move can't be placed
+    error: PlaceError,
+  },
+  /// The move was played by the wrong player
+  WrongPlayer {
+    /// Index of the move
+    ply: usize,
+    /// Player that was supposed to move
+    expected: Player,
+  },
+  /// The move was played after the game already ended
+  GameEnded {
+    /// Index of the move
+    ply: usize,
+  },
+  /// The board to replay the game on can't be created
+  InvalidBoard(Error),
+}
+
+impl fmt::Display for ReplayError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ReplayError::Illegal { ply, error } => write!(f, "illegal move {ply}: {error}"),
+      ReplayError::WrongPlayer { ply, expected } => {
+        write!(f, "move {ply} should have been played by {expected}")
+      },
+      ReplayError::GameEnded { ply } => write!(f, "move {ply} was played after the game ended"),
+      ReplayError::InvalidBoard(error) => write!(f, "{error}"),
+    }
+  }
+}
+impl error::Error for ReplayError {}
@@ -1,52 +1,161 @@
 use std::{
   iter::Sum,
-  ops::{Add, AddAssign, BitOr, BitOrAssign, Index, IndexMut},
+  ops::{Add, AddAssign, BitOr, BitOrAssign, Index, IndexMut, Sub, SubAssign},
 };
 
-use super::super::{player::Player, Score};
+use super::super::{player::Player, Score, MAX_EVAL};
 
-/// Return score and win state for the given shape
+/// Scores of the shapes used by the evaluation
 ///
-/// Shape is defined by number of consecutive symbols, number of open ends and
-/// if the shape contains a hole (in that case it is included in consecutive).
-pub fn shape_score(consecutive: u8, open_ends: u8, has_hole: bool) -> (Score, bool) {
-  if has_hole {
-    return match consecutive {
-      5.. => (40_000, false),
+/// Allows tuning the style of the engine without recompiling. The default
+/// reproduces the built-in weights.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Heuristic {
+  /// Five or more in a row, i.e. a win
+  pub five: Score,
+  /// Four with both ends open
+  pub open_four: Score,
+  /// Four with one end open
+  pub four: Score,
+  /// Three with both ends open
+  pub open_three: Score,
+  /// Three with one end open
+  pub three: Score,
+  /// Two with both ends open
+  pub open_two: Score,
+  /// Penalty per squared distance of a move from the center of the board
+  ///
+  /// Pulls the play towards the center, where there is more room for lines.
+  /// Only matters among moves with similar shape scores. Zero disables it.
+  pub center_bias: Score,
+  /// Bonus for a move making a four together with another four or an open
+  /// three, see [`Board::double_threat`](crate::Board::double_threat)
+  ///
+  /// Only used to score moves, not positions. Zero disables it.
+  pub double_threat: Score,
+  /// Four stones broken by a hole, like `xx-xx`, completed by filling it
+  pub split_four: Score,
+  /// Three stones broken by a hole with both ends open, like `-x-xx-`
+  pub open_split_three: Score,
+  /// Three stones broken by a hole with one end open
+  pub split_three: Score,
+  /// Detect shapes broken by two holes, like `x-x-x`, scored as split threes
+  ///
+  /// Otherwise the second hole ends the shape.
+  pub two_holes: bool,
+}
+impl Heuristic {
+  /// The built-in weights
+  pub const DEFAULT: Heuristic = Heuristic {
+    five: 100_000_000,
+    open_four: 10_000_000,
+    four: 100_000,
+    open_three: 5_000_000,
+    three: 10_000,
+    open_two: 2_000,
+    center_bias: 20,
+    double_threat: 10_000_000,
+    split_four: 40_000,
+    open_split_three: 20_000,
+    split_three: 500,
+    two_holes: false,
+  };
+
+  /// Check if the shape scores are the default ones, so the cached
+  /// evaluation can be used.
+  pub(crate) fn has_default_shapes(&self) -> bool {
+    Heuristic {
+      center_bias: Self::DEFAULT.center_bias,
+      double_threat: Self::DEFAULT.double_threat,
+      ..*self
+    } == Self::DEFAULT
+  }
+
+  /// Return score and win state for the given shape
+  ///
+  /// Shape is defined by number of consecutive symbols, number of open ends
+  /// and if the shape contains a hole (in that case it is included in
+  /// consecutive).
+  pub fn shape_score(&self, consecutive: u8, open_ends: u8, has_hole: bool) -> (Score, bool) {
+    if has_hole {
+      return (
+        self.split_shape_score(consecutive.saturating_sub(1), open_ends),
+        false,
+      );
+    }
+
+    match consecutive {
+      5.. => (self.five, true),
       4 => match open_ends {
-        2 => (20_000, false),
-        1 => (500, false),
+        2 => (self.open_four, false),
+        1 => (self.four, false),
+        _ => (0, false),
+      },
+      3 => match open_ends {
+        2 => (self.open_three, false),
+        1 => (self.three, false),
+        _ => (0, false),
+      },
+      2 => match open_ends {
+        2 => (self.open_two, false),
         _ => (0, false),
       },
       _ => (0, false),
-    };
+    }
   }
 
-  match consecutive {
-    5.. => (100_000_000, true),
-    4 => match open_ends {
-      2 => (10_000_000, false),
-      1 => (100_000, false),
-      _ => (0, false),
-    },
-    3 => match open_ends {
-      2 => (5_000_000, false),
-      1 => (10_000, false),
-      _ => (0, false),
-    },
-    2 => match open_ends {
-      2 => (2_000, false),
-      _ => (0, false),
-    },
-    _ => (0, false),
+  /// Return score for a shape broken by holes, `stones` being the number of
+  /// stones without the holes
+  pub fn split_shape_score(&self, stones: u8, open_ends: u8) -> Score {
+    match stones {
+      4.. => self.split_four,
+      3 => match open_ends {
+        2 => self.open_split_three,
+        1 => self.split_three,
+        _ => 0,
+      },
+      _ => 0,
+    }
+  }
+
+  /// Return score and win state for a shape with the given number of holes,
+  /// which are included in `consecutive`.
+  pub(crate) fn holed_shape_score(
+    &self,
+    consecutive: u8,
+    open_ends: u8,
+    holes: u8,
+  ) -> (Score, bool) {
+    match holes {
+      0 | 1 => self.shape_score(consecutive, open_ends, holes == 1),
+      // filling one of the holes makes at most a split four
+      _ => (
+        self.split_shape_score((consecutive - holes).min(3), open_ends),
+        false,
+      ),
+    }
   }
 }
+impl Default for Heuristic {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
+
+/// Clamp a sum of shape scores to [`MAX_EVAL`].
+pub fn clamp_score(score: i64) -> Score {
+  score.clamp(-i64::from(MAX_EVAL), i64::from(MAX_EVAL)) as Score
+}
 
+/// Shape scores of both players
+///
+/// Summed in 64 bits, so even a board full of fives can't overflow. Use
+/// [`clamp_score`] to get a [`Score`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct EvalScore(pub Score, pub Score);
+pub struct EvalScore(pub i64, pub i64);
 
 impl Index<Player> for EvalScore {
-  type Output = Score;
+  type Output = i64;
   fn index(&self, player: Player) -> &Self::Output {
     match player {
       Player::X => &self.0,
@@ -78,6 +187,20 @@ impl AddAssign for EvalScore {
   }
 }
 
+impl Sub for EvalScore {
+  type Output = Self;
+  fn sub(self, other: Self) -> Self {
+    Self(self.0 - other.0, self.1 - other.1)
+  }
+}
+
+impl SubAssign for EvalScore {
+  fn sub_assign(&mut self, other: Self) {
+    self.0 -= other.0;
+    self.1 -= other.1;
+  }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct EvalWin(pub bool, pub bool);
 
@@ -136,12 +259,72 @@ impl Sum for Eval {
   }
 }
 
+/// Evaluation of every sequence on a board, kept in sync with its tiles
+///
+/// Totals are maintained incrementally, so reading the whole-board evaluation
+/// doesn't need to walk all the sequences.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EvalCache {
+  sequences: Box<[Eval]>,
+  score: EvalScore,
+  /// number of sequences containing a win for X and O respectively
+  wins: (u32, u32),
+}
+
+impl EvalCache {
+  /// Create cache from evaluations of all sequences
+  pub fn new(sequences: Box<[Eval]>) -> Self {
+    let mut score = EvalScore::default();
+    let mut wins = (0, 0);
+
+    for eval in &*sequences {
+      score += eval.score;
+      wins.0 += u32::from(eval.win.0);
+      wins.1 += u32::from(eval.win.1);
+    }
+
+    EvalCache {
+      sequences,
+      score,
+      wins,
+    }
+  }
+
+  /// Get cached evaluation of a single sequence
+  pub fn get(&self, index: usize) -> Eval {
+    self.sequences[index]
+  }
+
+  /// Replace evaluation of a single sequence and update the totals
+  pub fn update(&mut self, index: usize, eval: Eval) {
+    let old = std::mem::replace(&mut self.sequences[index], eval);
+
+    self.score -= old.score;
+    self.score += eval.score;
+
+    self.wins.0 = self.wins.0 - u32::from(old.win.0) + u32::from(eval.win.0);
+    self.wins.1 = self.wins.1 - u32::from(old.win.1) + u32::from(eval.win.1);
+  }
+
+  /// Get evaluation of the whole board
+  pub fn total(&self) -> Eval {
+    Eval {
+      score: self.score,
+      win: EvalWin(self.wins.0 > 0, self.wins.1 > 0),
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   #[test]
   fn test_shape_score() {
+    let shape_score = |consecutive, open_ends, has_hole| {
+      Heuristic::DEFAULT.shape_score(consecutive, open_ends, has_hole)
+    };
+
     let shapes = [
       shape_score(0, 0, false),
       shape_score(1, 0, false),
@@ -172,4 +355,59 @@ mod tests {
       .enumerate()
       .for_each(|(i, (a, b))| assert!(a.0 <= b.0, "{i}: {a:?} {b:?}"));
   }
+
+  #[test]
+  fn test_default_weights() {
+    let Heuristic {
+      five,
+      open_four,
+      four,
+      open_three,
+      three,
+      open_two,
+      center_bias,
+      double_threat,
+      split_four,
+      open_split_three,
+      split_three,
+      two_holes,
+    } = Heuristic::DEFAULT;
+
+    assert_eq!(
+      [five, open_four, open_three, four, three, open_two],
+      [100_000_000, 10_000_000, 5_000_000, 100_000, 10_000, 2_000]
+    );
+    assert_eq!(center_bias, 20);
+    assert_eq!(double_threat, 10_000_000);
+    assert_eq!(
+      [split_four, open_split_three, split_three],
+      [40_000, 20_000, 500]
+    );
+    assert!(!two_holes);
+
+    // an open three is a threat to make an open four, so it outweighs a
+    // closed four
+    assert!(five > open_four && open_four > open_three && open_three > four);
+    assert!(four > three && three > open_two);
+
+    // a four-three wins by force, unlike two open threes
+    assert!(four + open_three + double_threat > 2 * open_three);
+
+    // only an unbroken five wins
+    for consecutive in 0..=10 {
+      for open_ends in 0..=2 {
+        assert_eq!(
+          Heuristic::DEFAULT
+            .shape_score(consecutive, open_ends, false)
+            .1,
+          consecutive >= 5
+        );
+        assert!(
+          !Heuristic::DEFAULT
+            .shape_score(consecutive, open_ends, true)
+            .1
+        );
+      }
+    }
+  }
 }
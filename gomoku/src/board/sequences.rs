@@ -1,53 +1,147 @@
+use super::TilePointer;
+
 pub type Sequence = Box<[usize]>;
 pub type Sequences = Box<[Sequence]>;
 
+/// Direction of a sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeqKind {
+  /// A row, from left to right
+  Row,
+  /// A column, from top to bottom
+  Col,
+  /// A diagonal going down and to the right (`\`), from top to bottom
+  DiagLR,
+  /// A diagonal going down and to the left (`/`), from top to bottom
+  DiagRL,
+}
+impl SeqKind {
+  /// All kinds of sequences, every tile lies on exactly one of each
+  pub const ALL: [SeqKind; 4] = [SeqKind::Row, SeqKind::Col, SeqKind::DiagLR, SeqKind::DiagRL];
+}
+
+/// Direction and position of a sequence on the board
+///
+/// The index is the `y` of a row, the `x` of a column and for diagonals the
+/// index used by [`Board::diagonal`](super::Board::diagonal) and
+/// [`Board::anti_diagonal`](super::Board::anti_diagonal).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeqInfo {
+  /// Direction of the sequence
+  pub kind: SeqKind,
+  /// Index of the sequence among the ones of the same kind
+  pub index: u16,
+}
+impl SeqInfo {
+  /// Get the sequence of the given kind going through the tile on a board
+  /// `width` tiles wide.
+  pub fn through(kind: SeqKind, ptr: TilePointer, width: u8) -> SeqInfo {
+    let x = u16::from(ptr.x);
+    let y = u16::from(ptr.y);
+
+    let index = match kind {
+      SeqKind::Row => y,
+      SeqKind::Col => x,
+      SeqKind::DiagLR => u16::from(width) - 1 - x + y,
+      SeqKind::DiagRL => x + y,
+    };
+
+    SeqInfo { kind, index }
+  }
+
+  /// Get the position of the sequence in the table made by [`generate`].
+  pub(crate) fn position(self, width: u8, height: u8) -> usize {
+    let w = usize::from(width);
+    let h = usize::from(height);
+    let index = usize::from(self.index);
+
+    match self.kind {
+      SeqKind::Row => index,
+      SeqKind::Col => h + index,
+      SeqKind::DiagRL => h + w + index,
+      SeqKind::DiagLR => h + w + (w + h - 1) + index,
+    }
+  }
+
+  /// Inverse of [`SeqInfo::position`].
+  pub(crate) fn from_position(position: usize, width: u8, height: u8) -> SeqInfo {
+    let w = usize::from(width);
+    let h = usize::from(height);
+    let diagonals = w + h - 1;
+
+    let (kind, index) = if position < h {
+      (SeqKind::Row, position)
+    } else if position < h + w {
+      (SeqKind::Col, position - h)
+    } else if position < h + w + diagonals {
+      (SeqKind::DiagRL, position - h - w)
+    } else {
+      (SeqKind::DiagLR, position - h - w - diagonals)
+    };
+
+    SeqInfo {
+      kind,
+      index: u16::try_from(index).expect("sequence index fits into u16"),
+    }
+  }
+}
+
 /// Create `Sequence` representing given row
-fn make_row(size: usize, y: usize) -> Sequence {
-  (0..size).map(|x| x + y * size).collect()
+fn make_row(width: usize, y: usize) -> Sequence {
+  (0..width).map(|x| x + y * width).collect()
 }
 
 /// Create `Sequence` representing given column
-fn make_col(size: usize, x: usize) -> Sequence {
-  (0..size).map(|y| x + y * size).collect()
+fn make_col(width: usize, height: usize, x: usize) -> Sequence {
+  (0..height).map(|y| x + y * width).collect()
 }
 
 /// Direction of a diagonal, always goes from top to bottom
 #[derive(Copy, Clone, Debug)]
 enum DiagonalDir {
-  LeftToRight = 1,
-  RightToLeft = -1,
+  LeftToRight,
+  RightToLeft,
 }
 
 /// Create `Sequence` representing given diagonal.
-/// `k` is the index of the diagonal and should be in the range `1..2 * size`.
-/// Note that the indexing starts at the respective top corner.
-fn make_diagonal(size: usize, k: usize, direction: DiagonalDir) -> Sequence {
-  let count = if k <= size { k } else { 2 * size - k };
-
-  let starting_idx = match (direction, k <= size) {
-    (DiagonalDir::LeftToRight, true) => size - k,
-    (DiagonalDir::LeftToRight, false) => (k - size) * size,
-    (DiagonalDir::RightToLeft, true) => k - 1,
-    (DiagonalDir::RightToLeft, false) => (k - size + 1) * size - 1,
-  };
-
-  let step = size
-    .checked_add_signed(direction as isize)
-    .expect("direction should be ±1 and size << usize::MAX");
-
-  (0..count).map(|i| starting_idx + i * step).collect()
+/// `k` is the index of the diagonal and should be in the range
+/// `1..width + height`. Note that the indexing starts at the respective top
+/// corner.
+fn make_diagonal(width: usize, height: usize, k: usize, direction: DiagonalDir) -> Sequence {
+  // every tile of the diagonal is `k - 1` steps away from the corner, split
+  // between going down and going sideways
+  let distance = k - 1;
+
+  let first_y = distance.saturating_sub(width - 1);
+  let last_y = distance.min(height - 1);
+
+  (first_y..=last_y)
+    .map(|y| {
+      let sideways = distance - y;
+
+      let x = match direction {
+        DiagonalDir::RightToLeft => sideways,
+        DiagonalDir::LeftToRight => width - 1 - sideways,
+      };
+
+      x + y * width
+    })
+    .collect()
 }
 
-/// Generate all possible sequences for the given board size
-pub fn generate(size: u8) -> Sequences {
-  let size = size as usize;
+/// Generate all possible sequences for the given board dimensions
+pub fn generate(width: u8, height: u8) -> Sequences {
+  let width = width as usize;
+  let height = height as usize;
 
-  let rows = (0..size).map(|y| make_row(size, y));
-  let columns = (0..size).map(|x| make_col(size, x));
+  let rows = (0..height).map(|y| make_row(width, y));
+  let columns = (0..width).map(|x| make_col(width, height, x));
 
   let diagonals = [DiagonalDir::RightToLeft, DiagonalDir::LeftToRight]
     .into_iter()
-    .flat_map(|direction| (1..2 * size).map(move |k| make_diagonal(size, k, direction)));
+    .flat_map(|direction| {
+      (1..width + height).map(move |k| make_diagonal(width, height, k, direction))
+    });
 
   rows.chain(columns).chain(diagonals).collect()
 }
@@ -142,7 +236,7 @@ mod tests {
 
   #[test]
   fn test_generate() {
-    let sequences = generate(10);
+    let sequences = generate(BOARD_SIZE, BOARD_SIZE);
 
     let expected = EXPECTED_ROWS
       .iter()
@@ -155,4 +249,52 @@ mod tests {
 
     assert_eq!(expected, sequences);
   }
+
+  #[test]
+  fn test_generate_rectangular() {
+    // 0 1 2
+    // 3 4 5
+    let sequences = generate(3, 2);
+
+    let expected: [&[usize]; 13] = [
+      &[0, 1, 2],
+      &[3, 4, 5],
+      &[0, 3],
+      &[1, 4],
+      &[2, 5],
+      &[0],
+      &[1, 3],
+      &[2, 4],
+      &[5],
+      &[2],
+      &[1, 5],
+      &[0, 4],
+      &[3],
+    ];
+
+    assert_eq!(sequences.len(), expected.len());
+
+    for (sequence, expected) in sequences.iter().zip(expected) {
+      assert_eq!(&**sequence, expected);
+    }
+  }
+
+  #[test]
+  fn test_seq_info() {
+    let (width, height) = (12, 9);
+    let sequences = generate(width, height);
+
+    for (position, sequence) in sequences.iter().enumerate() {
+      let info = SeqInfo::from_position(position, width, height);
+      assert_eq!(info.position(width, height), position);
+
+      for &tile in sequence {
+        let ptr = TilePointer {
+          x: (tile % usize::from(width)) as u8,
+          y: (tile / usize::from(width)) as u8,
+        };
+        assert_eq!(SeqInfo::through(info.kind, ptr, width), info);
+      }
+    }
+  }
 }
@@ -0,0 +1,76 @@
+use super::TilePointer;
+
+/// Rotation or reflection of a board
+///
+/// Every one of them can be expressed as an optional transposition (swapping
+/// the axes) followed by optional mirroring along each axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Symmetry {
+  pub transpose: bool,
+  pub flip_x: bool,
+  pub flip_y: bool,
+}
+impl Symmetry {
+  pub const IDENTITY: Symmetry = Symmetry::new(false, false, false);
+  pub const ROTATE_90: Symmetry = Symmetry::new(true, true, false);
+  pub const MIRROR_HORIZONTAL: Symmetry = Symmetry::new(false, true, false);
+  pub const MIRROR_VERTICAL: Symmetry = Symmetry::new(false, false, true);
+
+  const fn new(transpose: bool, flip_x: bool, flip_y: bool) -> Self {
+    Symmetry {
+      transpose,
+      flip_x,
+      flip_y,
+    }
+  }
+
+  /// Get all symmetries of a board with the given dimensions, starting with
+  /// the identity.
+  ///
+  /// Transpositions change the shape of rectangular boards, so they are
+  /// included only for square ones.
+  pub fn all(width: u8, height: u8) -> impl Iterator<Item = Symmetry> {
+    Symmetry::dihedral().filter(move |symmetry| width == height || !symmetry.transpose)
+  }
+
+  /// Get all 8 rotations and reflections, starting with the identity.
+  pub fn dihedral() -> impl Iterator<Item = Symmetry> {
+    (0..8).map(|bits| Symmetry::new(bits & 4 != 0, bits & 1 != 0, bits & 2 != 0))
+  }
+
+  /// Get dimensions of the board after applying the symmetry.
+  pub fn dimensions(self, width: u8, height: u8) -> (u8, u8) {
+    if self.transpose {
+      (height, width)
+    } else {
+      (width, height)
+    }
+  }
+
+  /// Get the symmetry undoing this one.
+  #[cfg_attr(not(feature = "fen"), allow(dead_code))]
+  pub fn inverse(self) -> Symmetry {
+    // transposing swaps which axis each flip applies to
+    if self.transpose {
+      Symmetry::new(true, self.flip_y, self.flip_x)
+    } else {
+      self
+    }
+  }
+
+  /// Move the pointer on a board with the given dimensions.
+  pub fn apply(self, ptr: TilePointer, width: u8, height: u8) -> TilePointer {
+    let (width, height) = self.dimensions(width, height);
+
+    let TilePointer { x, y } = if self.transpose {
+      TilePointer { x: ptr.y, y: ptr.x }
+    } else {
+      ptr
+    };
+
+    TilePointer {
+      x: if self.flip_x { width - 1 - x } else { x },
+      y: if self.flip_y { height - 1 - y } else { y },
+    }
+  }
+}
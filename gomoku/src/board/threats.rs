@@ -0,0 +1,388 @@
+use super::{Board, Player, TilePointer};
+
+/// A single threat on one line of the board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Threat {
+  /// Stones forming the threat
+  pub stones: Vec<TilePointer>,
+  /// Empty tiles upgrading the threat
+  ///
+  /// For fours these complete a five, for open threes they make an open four.
+  /// Empty for fives.
+  pub completions: Vec<TilePointer>,
+}
+
+/// Threats of a single player
+///
+/// Split shapes (e.g. `x-xx` or `xx-xx`) are included, the gap being one of
+/// the completions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Threats {
+  /// Threes that can become an open four in one move
+  pub open_threes: Vec<Threat>,
+  /// Fours that can be completed to a five on exactly one tile
+  pub fours: Vec<Threat>,
+  /// Fours that can be completed to a five on two or more tiles
+  pub open_fours: Vec<Threat>,
+  /// Already completed fives
+  pub fives: Vec<Threat>,
+}
+impl Threats {
+  /// Check if there are no threats at all.
+  pub fn is_empty(&self) -> bool {
+    self.open_threes.is_empty()
+      && self.fours.is_empty()
+      && self.open_fours.is_empty()
+      && self.fives.is_empty()
+  }
+}
+
+/// Two threats made by a single move on different lines
+///
+/// The opponent can block only one of them, so these usually decide the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoubleThreat {
+  /// Two fours
+  FourFour,
+  /// A four and an open three
+  FourThree,
+}
+
+/// Threat a single stone makes on one line
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LineThreat {
+  None,
+  OpenThree,
+  Four,
+}
+
+/// Add `completion` to the threat made of `stones`, creating it if needed.
+fn add_completion(threats: &mut Vec<Threat>, stones: Vec<TilePointer>, completion: TilePointer) {
+  if let Some(threat) = threats.iter_mut().find(|threat| threat.stones == stones) {
+    if !threat.completions.contains(&completion) {
+      threat.completions.push(completion);
+    }
+  } else {
+    threats.push(Threat {
+      stones,
+      completions: vec![completion],
+    });
+  }
+}
+
+impl Board {
+  /// Find all threats of the given player.
+  ///
+  /// Returns coordinates of both the stones forming each threat and the tiles
+  /// upgrading it.
+  pub fn threats(&self, player: Player) -> Threats {
+    let mut threats = Threats::default();
+
+    for sequence in self.sequences() {
+      let mut fours = Vec::new();
+      let mut threes = Vec::new();
+
+      let tiles = sequence
+        .iter()
+        .map(|&index| (self.get_ptr_from_index(index), self.data[index]))
+        .collect::<Vec<_>>();
+
+      let stones = |window: &[(TilePointer, Option<Player>)]| {
+        window
+          .iter()
+          .filter(|(_, tile)| *tile == Some(player))
+          .map(|(ptr, _)| *ptr)
+          .collect::<Vec<_>>()
+      };
+      let empty = |window: &[(TilePointer, Option<Player>)]| {
+        window
+          .iter()
+          .filter(|(_, tile)| tile.is_none())
+          .map(|(ptr, _)| *ptr)
+          .collect::<Vec<_>>()
+      };
+
+      // fives and fours: five tiles with at most one empty and no opponent
+      for window in tiles.windows(5) {
+        let stones = stones(window);
+
+        match (stones.len(), &empty(window)[..]) {
+          (5, _) if !threats.fives.iter().any(|five| five.stones == stones) => {
+            threats.fives.push(Threat {
+              stones,
+              completions: Vec::new(),
+            });
+          },
+          (4, &[gap]) => add_completion(&mut fours, stones, gap),
+          _ => {},
+        }
+      }
+
+      // open threes: `-xxx--`, `--xxx-`, `-xx-x-` and `-x-xx-`, i.e. three
+      // stones and one gap enclosed by two empty tiles
+      for window in tiles.windows(6) {
+        let (ends, inner) = ([window[0], window[5]], &window[1..5]);
+
+        if ends.iter().any(|(_, tile)| tile.is_some()) {
+          continue;
+        }
+
+        let stones = stones(inner);
+
+        if let (3, &[gap]) = (stones.len(), &empty(inner)[..]) {
+          add_completion(&mut threes, stones, gap);
+        }
+      }
+
+      // stones of an already completed five aren't a four anymore
+      fours.retain(|four: &Threat| {
+        !threats
+          .fives
+          .iter()
+          .any(|five| four.stones.iter().all(|stone| five.stones.contains(stone)))
+      });
+
+      for four in fours {
+        if four.completions.len() >= 2 {
+          threats.open_fours.push(four);
+        } else {
+          threats.fours.push(four);
+        }
+      }
+
+      threats.open_threes.extend(threes);
+    }
+
+    threats
+  }
+
+  /// Check if placing a stone of `player` at the empty `ptr` makes two threats
+  /// at once.
+  ///
+  /// Only threats including the new stone count and each has to be on a
+  /// different line through it, e.g. `x-x-x` on a single line is just a four.
+  /// Moves completing a five are not double threats, they win already.
+  pub fn double_threat(&self, ptr: TilePointer, player: Player) -> Option<DoubleThreat> {
+    let placed = Self::get_index(self.width, ptr);
+
+    let tile_at = |index| {
+      if index == placed {
+        Some(player)
+      } else {
+        self.data[index]
+      }
+    };
+
+    let mut fours = 0;
+    let mut threes = 0;
+
+    for sequence in self.relevant_sequences(ptr) {
+      match Self::line_threat(sequence, placed, player, tile_at) {
+        None => return None,
+        Some(LineThreat::Four) => fours += 1,
+        Some(LineThreat::OpenThree) => threes += 1,
+        Some(LineThreat::None) => {},
+      }
+    }
+
+    match (fours, threes) {
+      (2.., _) => Some(DoubleThreat::FourFour),
+      (1, 1..) => Some(DoubleThreat::FourThree),
+      _ => None,
+    }
+  }
+
+  /// Find the strongest threat including the stone at `placed` in the
+  /// sequence, same shapes as in [`Board::threats`].
+  ///
+  /// Returns `None` if the stone completes a five.
+  fn line_threat(
+    sequence: &[usize],
+    placed: usize,
+    player: Player,
+    tile_at: impl Fn(usize) -> Option<Player>,
+  ) -> Option<LineThreat> {
+    let position = sequence
+      .iter()
+      .position(|&index| index == placed)
+      .expect("the sequence goes through the tile");
+
+    // only windows covering the stone matter
+    let windows = |size: usize| {
+      let first = (position + 1).saturating_sub(size);
+
+      sequence
+        .windows(size)
+        .skip(first)
+        .take(position + 1 - first)
+    };
+    let count = |window: &[usize], tile: Option<Player>| {
+      window
+        .iter()
+        .filter(|&&index| tile_at(index) == tile)
+        .count()
+    };
+
+    let mut threat = LineThreat::None;
+
+    for window in windows(5) {
+      match (count(window, Some(player)), count(window, None)) {
+        (5, _) => return None,
+        (4, 1) => threat = LineThreat::Four,
+        _ => {},
+      }
+    }
+
+    if threat == LineThreat::None {
+      for window in windows(6) {
+        let (ends, inner) = ([window[0], window[5]], &window[1..5]);
+
+        if ends.iter().all(|&index| tile_at(index).is_none())
+          && inner.contains(&placed)
+          && count(inner, Some(player)) == 3
+          && count(inner, None) == 1
+        {
+          threat = LineThreat::OpenThree;
+        }
+      }
+    }
+
+    Some(threat)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+
+  fn tiles(coords: &[(u8, u8)]) -> Vec<TilePointer> {
+    coords.iter().map(|&(x, y)| TilePointer { x, y }).collect()
+  }
+
+  #[test]
+  fn test_threats() {
+    let board = Board::from_str(
+      "-xxx-----
+---------
+-x-xx----
+---------
+oxxxx----
+---------
+-xxxx----
+---------
+xxxxx----",
+    )
+    .unwrap();
+
+    let threats = board.threats(Player::X);
+
+    assert_eq!(
+      threats.open_threes,
+      vec![
+        Threat {
+          stones: tiles(&[(1, 0), (2, 0), (3, 0)]),
+          // a1 would make a four on the edge, which isn't open
+          completions: tiles(&[(4, 0)]),
+        },
+        Threat {
+          stones: tiles(&[(1, 2), (3, 2), (4, 2)]),
+          completions: tiles(&[(2, 2)]),
+        },
+      ]
+    );
+    assert_eq!(
+      threats.fours,
+      vec![Threat {
+        stones: tiles(&[(1, 4), (2, 4), (3, 4), (4, 4)]),
+        completions: tiles(&[(5, 4)]),
+      }]
+    );
+    assert_eq!(
+      threats.open_fours,
+      vec![Threat {
+        stones: tiles(&[(1, 6), (2, 6), (3, 6), (4, 6)]),
+        completions: tiles(&[(0, 6), (5, 6)]),
+      }]
+    );
+    assert_eq!(
+      threats.fives,
+      vec![Threat {
+        stones: tiles(&[(0, 8), (1, 8), (2, 8), (3, 8), (4, 8)]),
+        completions: Vec::new(),
+      }]
+    );
+
+    assert!(board.threats(Player::O).is_empty());
+  }
+
+  #[test]
+  fn test_split_four() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+--xx-xx--
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let threats = board.threats(Player::X);
+
+    assert_eq!(
+      threats.fours,
+      vec![Threat {
+        stones: tiles(&[(2, 4), (3, 4), (5, 4), (6, 4)]),
+        completions: tiles(&[(4, 4)]),
+      }]
+    );
+    assert!(threats.open_fours.is_empty());
+    assert!(threats.open_threes.is_empty());
+  }
+
+  #[test]
+  fn test_double_threat() {
+    // e5 makes a four in the column and an open three on the row
+    let mut board = Board::from_str(
+      "----o----
+----x----
+----x----
+----x----
+-----xx--
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let fork = TilePointer { x: 4, y: 4 };
+    assert_eq!(
+      board.double_threat(fork, Player::X),
+      Some(DoubleThreat::FourThree)
+    );
+    assert_eq!(board.double_threat(fork, Player::O), None);
+
+    // a threat on a single line isn't enough
+    assert_eq!(
+      board.double_threat(TilePointer { x: 3, y: 4 }, Player::X),
+      None
+    );
+
+    // fours on two different lines
+    board.set_tile(TilePointer { x: 7, y: 4 }, Some(Player::X));
+    assert_eq!(
+      board.double_threat(fork, Player::X),
+      Some(DoubleThreat::FourFour)
+    );
+
+    // completing a five wins on its own
+    board.set_tile(TilePointer { x: 4, y: 5 }, Some(Player::X));
+    assert_eq!(board.double_threat(fork, Player::X), None);
+  }
+}
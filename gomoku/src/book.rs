@@ -0,0 +1,209 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use crate::{utils::parse_fen_with_state, Board, Player, TilePointer};
+
+/// Moves to play in known positions instead of searching
+///
+/// Positions are stored in their [canonical](Board::canonical) form, so a
+/// single entry covers all rotations and reflections of the position.
+///
+/// The text format has one position per line, a FEN string as accepted by
+/// [`Board::from_fen`] followed by the move in the `h8` form. The move is
+/// played only by the side to move in the FEN, which the short form derives
+/// from the stone count. Empty lines and lines starting with `#` are skipped.
+///
+/// ```text
+/// # answer the center opening diagonally
+/// 15|///////7x///////|o|1 i9
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OpeningBook {
+  /// Move for every canonical position, keyed by its dimensions,
+  /// [Zobrist hash](Board::zobrist) and the side to move
+  moves: HashMap<(u8, u8, u64, Player), TilePointer>,
+}
+impl OpeningBook {
+  /// Create an empty book.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Parse a book from text, see [`OpeningBook`] for the format.
+  ///
+  /// # Errors
+  /// Returns an error with the line number if a line isn't a valid position
+  /// followed by a move to an empty tile.
+  pub fn parse(input: &str) -> Result<Self, Box<dyn Error>> {
+    let mut book = OpeningBook::new();
+
+    for (index, line) in input.lines().enumerate() {
+      let line = line.trim();
+
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let mut parse_entry = || -> Result<(), Box<dyn Error>> {
+        let (fen, tile) = line
+          .rsplit_once(char::is_whitespace)
+          .ok_or("expected a position and a move")?;
+
+        let (board, side, _) = parse_fen_with_state(fen.trim())?;
+        book.insert(&board, side, tile.parse()?)?;
+        Ok(())
+      };
+
+      if let Err(err) = parse_entry() {
+        return Err(format!("line {}: {err}", index + 1).into());
+      }
+    }
+
+    Ok(book)
+  }
+
+  /// Read a book from a file, see [`OpeningBook`] for the format.
+  ///
+  /// # Errors
+  /// Returns an error if the file can't be read or isn't a valid book.
+  pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+    Self::parse(&fs::read_to_string(path)?)
+  }
+
+  /// Add the move for `side` to play in the position, replacing any previous
+  /// one.
+  ///
+  /// # Errors
+  /// Returns an error if the tile is outside of the board or occupied.
+  pub fn insert(&mut self, board: &Board, side: Player, tile: TilePointer) -> Result<(), String> {
+    if board.get(tile) != Some(&None) {
+      return Err(format!("{tile} is not an empty tile"));
+    }
+
+    let symmetry = board.canonical_symmetry();
+    let canonical = board.transform(symmetry);
+
+    self.moves.insert(
+      Self::key(&canonical, side),
+      symmetry.apply(tile, board.width(), board.height()),
+    );
+
+    Ok(())
+  }
+
+  /// Get the move for `side` to play in the position, if it is in the book.
+  ///
+  /// A move to a tile that isn't empty, which can only come from a hash
+  /// collision, is never returned.
+  pub fn get(&self, board: &Board, side: Player) -> Option<TilePointer> {
+    let symmetry = board.canonical_symmetry();
+    let canonical = board.transform(symmetry);
+
+    let tile = self.moves.get(&Self::key(&canonical, side))?;
+
+    let tile = symmetry
+      .inverse()
+      .apply(*tile, canonical.width(), canonical.height());
+
+    (board.get(tile) == Some(&None)).then_some(tile)
+  }
+
+  /// Get the number of positions in the book.
+  pub fn len(&self) -> usize {
+    self.moves.len()
+  }
+
+  /// Check if the book has no positions.
+  pub fn is_empty(&self) -> bool {
+    self.moves.is_empty()
+  }
+
+  fn key(board: &Board, side: Player) -> (u8, u8, u64, Player) {
+    (board.width(), board.height(), board.zobrist(), side)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+  use crate::{utils::to_fen_string, Engine, Player, Verdict};
+
+  const BOARD: &str = "---------
+---------
+---------
+---------
+----x----
+---------
+---------
+---------
+---------";
+
+  #[test]
+  fn test_book_symmetries() {
+    let board = Board::from_str(BOARD).unwrap();
+    let reply = TilePointer { x: 5, y: 3 };
+
+    let book = OpeningBook::parse(&format!(
+      "# the diagonal reply\n\n{} {reply}\n",
+      to_fen_string(&board)
+    ))
+    .unwrap();
+    assert_eq!(book.len(), 1);
+    assert_eq!(book.get(&board, Player::O), Some(reply));
+
+    // the book move belongs to the side to move only
+    assert_eq!(book.get(&board, Player::X), None);
+
+    // the reply follows the position through every symmetry
+    let mut expected = board.clone();
+    expected.set_tile(reply, Some(Player::O));
+
+    for variant in board.symmetries() {
+      let tile = book.get(&variant, Player::O).unwrap();
+
+      let mut played = variant.clone();
+      played.set_tile(tile, Some(Player::O));
+
+      assert!(played.symmetries().contains(&expected));
+    }
+
+    assert_eq!(book.get(&Board::new_empty(9).unwrap(), Player::O), None);
+
+    let err = OpeningBook::parse("9|////4x//// i9\n9|// e5").unwrap_err();
+    assert!(err.to_string().starts_with("line 2"), "{err}");
+  }
+
+  #[test]
+  fn test_book_skips_occupied_tiles() {
+    let board = Board::from_str(BOARD).unwrap();
+    let canonical = board.transform(board.canonical_symmetry());
+    let (occupied, _) = canonical.occupied_tiles().next().unwrap();
+
+    // what a colliding entry would look like
+    let mut book = OpeningBook::new();
+    book
+      .moves
+      .insert(OpeningBook::key(&canonical, Player::O), occupied);
+
+    assert_eq!(book.get(&board, Player::O), None);
+  }
+
+  #[test]
+  fn test_engine_plays_book_move() {
+    let mut board = Board::from_str(BOARD).unwrap();
+    let reply = TilePointer { x: 3, y: 3 };
+
+    let path = std::env::temp_dir().join("gomoku-test-book.txt");
+    fs::write(&path, format!("{} {reply}", to_fen_string(&board))).unwrap();
+    let book = OpeningBook::from_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let engine = Engine::builder().opening_book(book).build().unwrap();
+    let outcome = engine.decide(&mut board, Player::O).unwrap();
+
+    assert_eq!(outcome.verdict, Verdict::Book);
+    assert_eq!(outcome.best_move().map(|move_| move_.tile), Some(reply));
+    assert_eq!(*board.get_tile(reply), Some(Player::O));
+  }
+}
@@ -0,0 +1,1856 @@
+#[cfg(feature = "threads")]
+use std::thread::{self, JoinHandle};
+use std::{error::Error, fmt, sync::Arc, time::Duration};
+
+#[cfg(feature = "threads")]
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+#[cfg(feature = "fen")]
+use crate::OpeningBook;
+use crate::{
+  error::GomokuError,
+  minimax, minimax_among,
+  node::Node,
+  search,
+  utils::{monotonic_clock, splitmix64, CancelToken},
+  Board, Evaluator, Explanation, Heuristic, Limit, Move, PlaceError, Player, Rules, Score,
+  SearchEvent, SearchOutcome, Skill, State, Stats, TilePointer, Verdict,
+};
+
+/// How many time limits pondering can run for, see [`Engine::ponder`]
+#[cfg(feature = "threads")]
+const PONDER_TIME_LIMITS: u32 = 10;
+
+/// Configured instance of the engine
+///
+/// Owns its own thread pool, so multiple engines with different settings can
+/// coexist in one process. Cloning is cheap and shares the pool. Without the
+/// `threads` feature it searches on the calling thread instead.
+///
+/// Construct it using [`Engine::builder`].
+#[derive(Clone)]
+pub struct Engine {
+  #[cfg(feature = "threads")]
+  pool: Arc<ThreadPool>,
+  time_limit: Duration,
+  settings: Settings,
+}
+impl fmt::Debug for Engine {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Engine")
+      .field("threads", &self.threads())
+      .field("time_limit", &self.time_limit)
+      .field("settings", &self.settings)
+      .finish_non_exhaustive()
+  }
+}
+
+/// Settings of the search itself
+///
+/// Shared by [`Engine`] and the free functions, which use the defaults.
+#[derive(Clone, Default)]
+pub(crate) struct Settings {
+  pub rules: Rules,
+  pub heuristic: Heuristic,
+  /// Custom evaluator, `None` uses the built-in incremental evaluation
+  pub evaluator: Option<Arc<dyn Evaluator + Send + Sync>>,
+  pub tie_break: TieBreak,
+  pub skill: Skill,
+  /// Seed of the mistakes of lower skill levels
+  pub skill_seed: u64,
+  pub search: SearchConfig,
+  /// Search only one of the moves equivalent by a symmetry of the board
+  pub symmetry_dedup: bool,
+  /// Depth to dump the search tree to, `None` doesn't dump it
+  pub dump_tree: Option<u8>,
+  /// Callback for progress reports, `None` keeps the search silent
+  pub progress: Option<Arc<dyn Fn(SearchEvent) + Send + Sync>>,
+  /// Clock measuring the time limit, `None` uses [`Instant`](std::time::Instant)
+  pub clock: Option<fn() -> Duration>,
+  /// Extra condition stopping the search when it returns `true`
+  pub stop_when: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+  /// Moves to play without searching
+  #[cfg(feature = "fen")]
+  pub book: Option<Arc<OpeningBook>>,
+}
+impl Settings {
+  /// Evaluate the whole board for `player` with the configured evaluation.
+  pub fn evaluate(&self, board: &Board, player: Player) -> (Score, State) {
+    match &self.evaluator {
+      Some(evaluator) => evaluator.evaluate(board, player),
+      None => board.evaluate_for_with(player, &self.heuristic),
+    }
+  }
+
+  /// Get the current time on the configured clock.
+  pub fn now(&self) -> Duration {
+    self.clock.unwrap_or(monotonic_clock)()
+  }
+
+  /// Report progress, if anyone is listening.
+  ///
+  /// The event is constructed only when needed.
+  pub fn emit(&self, event: impl FnOnce() -> SearchEvent) {
+    if let Some(progress) = &self.progress {
+      progress(event());
+    }
+  }
+}
+impl fmt::Debug for Settings {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut debug = f.debug_struct("Settings");
+    debug
+      .field("rules", &self.rules)
+      .field("heuristic", &self.heuristic)
+      .field("tie_break", &self.tie_break)
+      .field("skill", &self.skill)
+      .field("skill_seed", &self.skill_seed)
+      .field("search", &self.search)
+      .field("symmetry_dedup", &self.symmetry_dedup)
+      .field("dump_tree", &self.dump_tree)
+      .field("evaluator", &self.evaluator.as_ref().map(|_| "custom"))
+      .field("progress", &self.progress.is_some())
+      .field("clock", &self.clock.map(|_| "custom"))
+      .field("stop_when", &self.stop_when.is_some());
+
+    #[cfg(feature = "fen")]
+    debug.field(
+      "book",
+      &self
+        .book
+        .as_ref()
+        .map(|book| format!("{} positions", book.len())),
+    );
+
+    debug.finish()
+  }
+}
+impl Engine {
+  /// Create a builder with default settings.
+  pub fn builder() -> EngineBuilder {
+    EngineBuilder::default()
+  }
+
+  /// Find the best move for `player` and play it on the board.
+  ///
+  /// If the board is full, there is no move to play and the verdict is
+  /// [`Verdict::Draw`](crate::Verdict::Draw), unless the rules don't allow
+  /// draws, see [`Rules::can_draw`].
+  ///
+  /// # Errors
+  /// Returns an error if the engine failed to find a move. See [`GomokuError`]
+  /// for possible errors.
+  pub fn decide(&self, board: &mut Board, player: Player) -> Result<SearchOutcome, GomokuError> {
+    self.search(board, player, Limit::Time(self.time_limit))
+  }
+
+  /// Play the opening move for `player`, the center of an empty board.
+  ///
+  /// Frontends can call this on the first turn, as there is nothing to
+  /// search yet. The move isn't searched, so the verdict is
+  /// [`Verdict::Unclear`] and the stats are empty. If the board isn't empty,
+  /// it is the same as [`Engine::decide`].
+  ///
+  /// # Errors
+  /// Returns the same errors as [`Engine::decide`].
+  pub fn opening_move(
+    &self,
+    board: &mut Board,
+    player: Player,
+  ) -> Result<SearchOutcome, GomokuError> {
+    if board.stone_count(Player::X) > 0 || board.stone_count(Player::O) > 0 {
+      return self.decide(board, player);
+    }
+
+    let move_ = Move {
+      tile: board.center(),
+      score: 0,
+    };
+    board.set_tile(move_.tile, Some(player));
+
+    Ok(SearchOutcome {
+      best: Some(move_),
+      pv: vec![move_],
+      verdict: Verdict::Unclear,
+      stats: Stats::new(),
+    })
+  }
+
+  /// Start searching for the best move for `player` in the background.
+  ///
+  /// The search runs until the time limit or until it is cancelled using the
+  /// returned handle. Unlike [`Engine::decide`] the move isn't played, as the
+  /// board is copied for the search.
+  #[cfg(feature = "threads")]
+  pub fn decide_async(&self, board: &Board, player: Player) -> SearchHandle {
+    self.spawn_search(board.clone(), player, Limit::Time(self.time_limit))
+  }
+
+  /// Start thinking on the opponent's turn, assuming they play
+  /// `predicted_move`.
+  ///
+  /// `player` is the engine's side, the one to move after the predicted
+  /// move. The search runs until the handle is passed to
+  /// [`Engine::decide_pondered`], discarded or dropped, but at most ten times
+  /// the time limit, so the tree doesn't keep growing while the opponent
+  /// takes long to think.
+  ///
+  /// In a game loop, start pondering right after the engine's move, e.g. on
+  /// the expected reply from the best line reported by
+  /// [`SearchEvent::Finished`], then let the opponent think:
+  ///
+  /// ```no_run
+  /// # use gomoku_lib::{Board, Engine, Player, TilePointer};
+  /// # let engine = Engine::builder().build().unwrap();
+  /// # let mut board = Board::new_empty(15).unwrap();
+  /// # let predicted = TilePointer { x: 7, y: 7 };
+  /// # let opponent_move = TilePointer { x: 7, y: 7 };
+  /// let ponder = engine.ponder(&board, Player::O, predicted).unwrap();
+  ///
+  /// // ... wait for the opponent ...
+  /// board.place(opponent_move, Player::X).unwrap();
+  ///
+  /// // instant if the prediction was right
+  /// let outcome = engine.decide_pondered(&mut board, Player::O, ponder).unwrap();
+  /// ```
+  ///
+  /// # Errors
+  /// Returns [`GomokuError::IllegalMove`] if the predicted tile is out of
+  /// bounds or occupied.
+  #[cfg(feature = "threads")]
+  pub fn ponder(
+    &self,
+    board: &Board,
+    player: Player,
+    predicted_move: TilePointer,
+  ) -> Result<PonderHandle, GomokuError> {
+    let mut board = board.clone();
+    board
+      .place(predicted_move, !player)
+      .map_err(GomokuError::IllegalMove)?;
+
+    Ok(PonderHandle {
+      handle: self.spawn_search(
+        board.clone(),
+        player,
+        Limit::Time(self.time_limit.saturating_mul(PONDER_TIME_LIMITS)),
+      ),
+      board,
+      player,
+      predicted_move,
+    })
+  }
+
+  /// Find the best move for `player` and play it on the board, reusing the
+  /// pondering if the prediction was right.
+  ///
+  /// On a correct prediction the pondering is stopped and its result returned
+  /// right away, as long as it completed at least one depth. Otherwise the
+  /// pondering is discarded and this is the same as [`Engine::decide`].
+  ///
+  /// # Errors
+  /// Returns an error if the engine failed to find a move. See [`GomokuError`]
+  /// for possible errors.
+  ///
+  /// # Panics
+  /// Panics if the pondering thread panicked.
+  #[cfg(feature = "threads")]
+  pub fn decide_pondered(
+    &self,
+    board: &mut Board,
+    player: Player,
+    ponder: PonderHandle,
+  ) -> Result<SearchOutcome, GomokuError> {
+    if !ponder.is_hit(board, player) {
+      ponder.discard();
+      return self.decide(board, player);
+    }
+
+    let outcome = ponder.handle.stop()?;
+
+    // stopped before finishing the first depth, nothing to reuse
+    if outcome.stats.depth_reached == 0 {
+      return self.decide(board, player);
+    }
+
+    if let Some(move_) = outcome.best {
+      board.set_tile(move_.tile, Some(player));
+    }
+
+    Ok(outcome)
+  }
+
+  /// Run the search on its own thread inside the pool.
+  #[cfg(feature = "threads")]
+  fn spawn_search(&self, board: Board, player: Player, limit: Limit) -> SearchHandle {
+    let token = CancelToken::new();
+
+    let engine = self.clone();
+    let search_token = token.clone();
+
+    let thread = thread::spawn(move || {
+      engine
+        .pool
+        .install(|| minimax(&board, player, limit, &engine.settings, search_token))
+    });
+
+    SearchHandle {
+      token,
+      thread: Some(thread),
+    }
+  }
+
+  /// Find the best move for `player` searching exactly to the given depth and
+  /// play it on the board.
+  ///
+  /// Ignores the time limit. A depth of 0 is searched as 1, like in
+  /// [`decide_to_depth`](crate::decide_to_depth).
+  ///
+  /// # Errors
+  /// Returns an error if the engine failed to find a move. See [`GomokuError`]
+  /// for possible errors.
+  pub fn decide_to_depth(
+    &self,
+    board: &mut Board,
+    player: Player,
+    depth: u8,
+  ) -> Result<SearchOutcome, GomokuError> {
+    self.search(board, player, Limit::Depth(depth))
+  }
+
+  /// Find up to `n` best moves for `player`, best first.
+  ///
+  /// See [`analyze`](crate::analyze) for details.
+  ///
+  /// # Errors
+  /// Returns an error if the engine failed to find a move. See [`GomokuError`]
+  /// for possible errors.
+  pub fn analyze(&self, board: &Board, player: Player, n: usize) -> Result<Vec<Move>, GomokuError> {
+    let (nodes, ..) = self.install(|| {
+      search(
+        board,
+        player,
+        Limit::Time(self.time_limit),
+        &self.settings,
+        CancelToken::new(),
+      )
+    })?;
+
+    Ok(nodes.iter().take(n).map(Node::to_move).collect())
+  }
+
+  /// Find the best move for `player` among the `candidates` only and play it
+  /// on the board.
+  ///
+  /// Useful for focused analysis, e.g. checking whether a specific move is
+  /// sound. The search is the same as in [`Engine::decide`], except that the
+  /// opening book and forced blocks are skipped. Candidates forbidden by the
+  /// rules are ignored and duplicates are searched once.
+  ///
+  /// # Errors
+  /// Returns [`GomokuError::IllegalMove`] if any candidate is out of bounds
+  /// or occupied, otherwise the same errors as [`Engine::decide`].
+  pub fn decide_among(
+    &self,
+    board: &mut Board,
+    player: Player,
+    candidates: &[TilePointer],
+    time_limit: Duration,
+  ) -> Result<SearchOutcome, GomokuError> {
+    let mut tiles = Vec::with_capacity(candidates.len());
+
+    for &tile in candidates {
+      match board.get(tile) {
+        None => return Err(GomokuError::IllegalMove(PlaceError::OutOfBounds(tile))),
+        Some(Some(_)) => return Err(GomokuError::IllegalMove(PlaceError::Occupied(tile))),
+        Some(None) if !tiles.contains(&tile) => tiles.push(tile),
+        Some(None) => {},
+      }
+    }
+
+    let outcome = self.install(|| {
+      minimax_among(
+        board,
+        player,
+        Some(&tiles),
+        Limit::Time(time_limit),
+        &self.settings,
+        CancelToken::new(),
+      )
+    })?;
+
+    if let Some(move_) = outcome.best {
+      board.set_tile(move_.tile, Some(player));
+    }
+
+    Ok(outcome)
+  }
+
+  /// Explain why `player` would play at `tile`.
+  ///
+  /// Reports the threats the move makes and blocks and the line the engine
+  /// expects to follow, searched within the time limit.
+  ///
+  /// # Errors
+  /// Returns [`GomokuError::IllegalMove`] if the move can't be played or an
+  /// error if the search for the replies failed.
+  pub fn explain(
+    &self,
+    board: &Board,
+    player: Player,
+    tile: TilePointer,
+  ) -> Result<Explanation, GomokuError> {
+    let mut after = board.clone();
+    after
+      .place(tile, player)
+      .map_err(GomokuError::IllegalMove)?;
+
+    let (score, _) = self.settings.evaluate(&after, player);
+    let mut explanation = Explanation::new(board, player, tile, score);
+
+    if explanation.wins() || after.is_full() {
+      return Ok(explanation);
+    }
+
+    let outcome = self.install(|| {
+      minimax(
+        &after,
+        !player,
+        Limit::Time(self.time_limit),
+        &self.settings,
+        CancelToken::new(),
+      )
+    })?;
+
+    explanation
+      .pv
+      .extend(outcome.pv.iter().map(|move_| move_.tile));
+
+    if let Some(best) = outcome.best {
+      explanation.score = -best.score;
+    }
+
+    Ok(explanation)
+  }
+
+  fn search(
+    &self,
+    board: &mut Board,
+    player: Player,
+    limit: Limit,
+  ) -> Result<SearchOutcome, GomokuError> {
+    let outcome =
+      self.install(|| minimax(board, player, limit, &self.settings, CancelToken::new()))?;
+
+    if let Some(move_) = outcome.best {
+      board.set_tile(move_.tile, Some(player));
+    }
+
+    Ok(outcome)
+  }
+
+  /// Run `op` inside the pool.
+  #[cfg(feature = "threads")]
+  fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+    self.pool.install(op)
+  }
+
+  /// Run `op` on the current thread.
+  #[cfg(not(feature = "threads"))]
+  #[allow(clippy::unused_self)]
+  fn install<R>(&self, op: impl FnOnce() -> R) -> R {
+    op()
+  }
+
+  /// Get the time limit for a single search.
+  pub fn time_limit(&self) -> Duration {
+    self.time_limit
+  }
+
+  /// Get the rules the engine plays by.
+  pub fn rules(&self) -> Rules {
+    self.settings.rules
+  }
+
+  /// Get the shape scores used for evaluation.
+  pub fn heuristic(&self) -> Heuristic {
+    self.settings.heuristic
+  }
+
+  /// Get the way moves with equal score are chosen.
+  pub fn tie_break(&self) -> TieBreak {
+    self.settings.tie_break
+  }
+
+  /// Get the strength of the engine.
+  pub fn skill(&self) -> Skill {
+    self.settings.skill
+  }
+
+  /// Get the seed of the mistakes of lower skill levels.
+  pub fn skill_seed(&self) -> u64 {
+    self.settings.skill_seed
+  }
+
+  /// Get the breadth of the search.
+  pub fn search_config(&self) -> &SearchConfig {
+    &self.settings.search
+  }
+
+  /// Get the number of threads used for searching.
+  #[cfg(feature = "threads")]
+  pub fn threads(&self) -> usize {
+    self.pool.current_num_threads()
+  }
+
+  /// Get the number of threads used for searching, always 1 without the
+  /// `threads` feature.
+  #[cfg(not(feature = "threads"))]
+  #[allow(clippy::unused_self)]
+  pub fn threads(&self) -> usize {
+    1
+  }
+}
+
+/// Handle to a search running in the background
+///
+/// Created by [`Engine::decide_async`]. Dropping the handle cancels the
+/// search without waiting for it.
+#[cfg(feature = "threads")]
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SearchHandle {
+  token: CancelToken,
+  /// Always `Some` until joined
+  thread: Option<JoinHandle<Result<SearchOutcome, GomokuError>>>,
+}
+#[cfg(feature = "threads")]
+impl SearchHandle {
+  /// Ask the search to stop as soon as possible.
+  ///
+  /// The search still returns the best move found so far.
+  pub fn cancel(&self) {
+    self.token.cancel();
+  }
+
+  /// Check if the search has already finished.
+  pub fn is_finished(&self) -> bool {
+    self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+  }
+
+  /// Wait for the search to finish and return the outcome.
+  ///
+  /// # Errors
+  /// Returns an error if the engine failed to find a move. See
+  /// [`GomokuError`] for possible errors.
+  ///
+  /// # Panics
+  /// Panics if the search thread panicked.
+  pub fn join(mut self) -> Result<SearchOutcome, GomokuError> {
+    self
+      .thread
+      .take()
+      .expect("search thread already joined")
+      .join()
+      .expect("search thread panicked")
+  }
+
+  /// Cancel the search and wait for the best move found so far.
+  fn stop(self) -> Result<SearchOutcome, GomokuError> {
+    self.cancel();
+    self.join()
+  }
+}
+#[cfg(feature = "threads")]
+impl Drop for SearchHandle {
+  fn drop(&mut self) {
+    self.token.cancel();
+  }
+}
+
+/// Handle to the engine thinking on the opponent's turn
+///
+/// Created by [`Engine::ponder`] and consumed by [`Engine::decide_pondered`].
+#[cfg(feature = "threads")]
+#[derive(Debug)]
+pub struct PonderHandle {
+  handle: SearchHandle,
+  /// Board after the predicted move
+  board: Board,
+  player: Player,
+  predicted_move: TilePointer,
+}
+#[cfg(feature = "threads")]
+impl PonderHandle {
+  /// Get the move the opponent is expected to play.
+  pub fn predicted_move(&self) -> TilePointer {
+    self.predicted_move
+  }
+
+  /// Check if the prediction was right, that is `player` is to move on
+  /// exactly the pondered board.
+  pub fn is_hit(&self, board: &Board, player: Player) -> bool {
+    self.player == player && self.board == *board
+  }
+
+  /// Stop pondering and throw the results away.
+  ///
+  /// Same as dropping the handle.
+  pub fn discard(self) {
+    self.handle.cancel();
+  }
+}
+
+/// How to choose among moves with equal score
+///
+/// Whatever the choice, the result doesn't depend on the thread count.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak {
+  /// Prefer the first move in reading order, that is by row, then by column
+  #[default]
+  FirstStable,
+  /// Prefer the move closer to the center of the board
+  CenterPreferred,
+  /// Prefer a pseudo-random move, same seed always gives the same choice
+  Random(u64),
+}
+impl TieBreak {
+  /// Get the key used to order the tile, lower is preferred. Keys of
+  /// different tiles on the same board are distinct.
+  pub(crate) fn key(self, board: &Board, tile: TilePointer) -> u64 {
+    let index = u64::from(board.width()) * u64::from(tile.y) + u64::from(tile.x);
+
+    match self {
+      TieBreak::FirstStable => index,
+      TieBreak::CenterPreferred => {
+        let distance = board.squared_distance_from_center(tile) as u64;
+        (distance << 16) | index
+      },
+      // the top bits are random, the bottom ones keep the keys distinct
+      TieBreak::Random(seed) => (splitmix64(seed ^ index) << 16) | index,
+    }
+  }
+}
+
+/// Breadth of the search
+///
+/// After every depth only the most promising moves are kept, these set how
+/// many. Wider beams are stronger, but slower.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SearchConfig {
+  /// Factor of `sqrt(n)` top-level moves kept after every depth, but at least
+  /// 3 are always kept
+  ///
+  /// Lower [`Skill`] levels cap it further.
+  pub top_level_beam: f32,
+  /// Number of replies kept by a node, indexed by how deep the node has been
+  /// searched, starting at 2
+  ///
+  /// The last entry applies to all deeper nodes. The first one keeps at
+  /// least half of the replies, so a node doesn't miss an immediate threat.
+  /// Empty keeps all replies.
+  pub depth_beams: Vec<usize>,
+  /// Part of the time limit left to return the result
+  ///
+  /// The search stops this long before the time limit, or right after the
+  /// first depth if the limit is shorter.
+  pub safety_margin: Duration,
+  /// Whether to first try the reply that completed a five against a sibling
+  ///
+  /// A node stops looking at its replies once one of them completes a five,
+  /// so this only lowers the number of evaluated nodes, the result stays the
+  /// same.
+  pub killer_moves: bool,
+}
+impl SearchConfig {
+  /// Get how many of `count` replies a node searched to `depth` keeps.
+  pub(crate) fn replies_kept(&self, depth: u8, count: usize) -> usize {
+    debug_assert!(
+      depth >= 2,
+      "depth 0 or 1 means the children are yet to be initialized"
+    );
+
+    let index = usize::from(depth.saturating_sub(2));
+
+    let Some(&beam) = self.depth_beams.get(index).or(self.depth_beams.last()) else {
+      return count;
+    };
+
+    if index == 0 {
+      beam.max(count / 2)
+    } else {
+      beam.max(1)
+    }
+  }
+}
+impl Default for SearchConfig {
+  fn default() -> Self {
+    SearchConfig {
+      top_level_beam: 2.0,
+      depth_beams: vec![24, 16, 8, 8, 8, 8, 4, 2],
+      safety_margin: Duration::from_millis(10),
+      killer_moves: true,
+    }
+  }
+}
+
+/// Builder for [`Engine`]
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EngineBuilder {
+  threads: Option<usize>,
+  #[cfg(feature = "threads")]
+  pool: Option<Arc<ThreadPool>>,
+  time_limit: Duration,
+  settings: Settings,
+}
+impl EngineBuilder {
+  /// Set the number of threads to search with.
+  ///
+  /// Defaults to the number of logical CPUs. Ignored without the `threads`
+  /// feature.
+  #[must_use]
+  pub fn threads(mut self, threads: usize) -> Self {
+    self.threads = Some(threads);
+    self
+  }
+
+  /// Search in an existing thread pool instead of creating a new one.
+  ///
+  /// Lets several engines share one pool. Takes precedence over
+  /// [`EngineBuilder::threads`].
+  #[cfg(feature = "threads")]
+  #[must_use]
+  pub fn thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+    self.pool = Some(pool);
+    self
+  }
+
+  /// Set the time limit for a single search.
+  ///
+  /// Defaults to 1 second.
+  #[must_use]
+  pub fn time_limit(mut self, time_limit: Duration) -> Self {
+    self.time_limit = time_limit;
+    self
+  }
+
+  /// Set the rules to play by.
+  ///
+  /// Defaults to [`Rules::freestyle`].
+  #[must_use]
+  pub fn rules(mut self, rules: Rules) -> Self {
+    self.settings.rules = rules;
+    self
+  }
+
+  /// Set the shape scores used for evaluation.
+  ///
+  /// Defaults to [`Heuristic::DEFAULT`].
+  #[must_use]
+  pub fn heuristic(mut self, heuristic: Heuristic) -> Self {
+    self.settings.heuristic = heuristic;
+    self
+  }
+
+  /// Set how to choose among moves with equal score.
+  ///
+  /// Defaults to [`TieBreak::FirstStable`].
+  #[must_use]
+  pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+    self.settings.tie_break = tie_break;
+    self
+  }
+
+  /// Set the strength of the engine, see [`Skill`] for what each level
+  /// means.
+  ///
+  /// Defaults to [`Skill::Max`].
+  #[must_use]
+  pub fn skill(mut self, skill: Skill) -> Self {
+    self.settings.skill = skill;
+    self
+  }
+
+  /// Set the seed of the mistakes of lower skill levels.
+  ///
+  /// It's mixed with the hash of the position, so the same seed always makes
+  /// the same mistakes in the same positions. Defaults to 0.
+  #[must_use]
+  pub fn skill_seed(mut self, seed: u64) -> Self {
+    self.settings.skill_seed = seed;
+    self
+  }
+
+  /// Set the breadth of the search.
+  ///
+  /// Defaults to [`SearchConfig::default`].
+  #[must_use]
+  pub fn search_config(mut self, search: SearchConfig) -> Self {
+    self.settings.search = search;
+    self
+  }
+
+  /// Set whether to search only one of the moves that are equivalent by a
+  /// symmetry of the board, e.g. the corners of an empty board.
+  ///
+  /// Only has an effect on symmetric boards. Defaults to `false`.
+  #[must_use]
+  pub fn symmetry_dedup(mut self, symmetry_dedup: bool) -> Self {
+    self.settings.symmetry_dedup = symmetry_dedup;
+    self
+  }
+
+  /// Set a custom evaluation of positions.
+  ///
+  /// Overrides the [`heuristic`](Self::heuristic), unless the evaluator
+  /// uses it itself. Defaults to the built-in evaluation.
+  #[must_use]
+  pub fn evaluator(mut self, evaluator: Box<dyn Evaluator + Send + Sync>) -> Self {
+    self.settings.evaluator = Some(Arc::from(evaluator));
+    self
+  }
+
+  /// Send the search tree down to `max_depth` levels as
+  /// [`SearchEvent::Tree`] at the end of every search, for debugging.
+  ///
+  /// Requires a [`progress`](Self::progress) callback. The tree grows quickly
+  /// with depth, so keep it small. By default the tree isn't dumped.
+  #[must_use]
+  pub fn dump_tree(mut self, max_depth: u8) -> Self {
+    self.settings.dump_tree = Some(max_depth);
+    self
+  }
+
+  /// Set the clock measuring the time limit, returning the time since any
+  /// fixed point.
+  ///
+  /// Defaults to [`Instant`](std::time::Instant), which isn't available on
+  /// some targets, e.g. `wasm32-unknown-unknown`, so supply e.g.
+  /// `performance.now()` there.
+  #[must_use]
+  pub fn clock(mut self, clock: fn() -> Duration) -> Self {
+    self.settings.clock = Some(clock);
+    self
+  }
+
+  /// Set the opening book to play from before searching.
+  ///
+  /// Positions found in the book are answered instantly with
+  /// [`Verdict::Book`](crate::Verdict::Book). By default there is no book.
+  #[cfg(feature = "fen")]
+  #[must_use]
+  pub fn opening_book(mut self, book: OpeningBook) -> Self {
+    self.settings.book = Some(Arc::new(book));
+    self
+  }
+
+  /// Set a condition stopping the search once it returns `true`.
+  ///
+  /// It is checked along with the time limit, from all the search threads,
+  /// so it has to be cheap. Like with running out of time, the depth in
+  /// progress is thrown away and the first depth always completes. Together
+  /// with a [`progress`](Self::progress) callback this can stop the search
+  /// after an exact number of depths, e.g. in tests. By default only the
+  /// time limit and cancellation stop the search.
+  #[must_use]
+  pub fn stop_when(mut self, stop: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+    self.settings.stop_when = Some(Arc::new(stop));
+    self
+  }
+
+  /// Set a callback receiving progress reports during the search.
+  ///
+  /// By default the search is silent.
+  #[must_use]
+  pub fn progress(mut self, progress: impl Fn(SearchEvent) + Send + Sync + 'static) -> Self {
+    self.settings.progress = Some(Arc::new(progress));
+    self
+  }
+
+  /// Build the engine.
+  ///
+  /// # Errors
+  /// Returns an error if the thread pool couldn't be created.
+  pub fn build(self) -> Result<Engine, Box<dyn Error>> {
+    Ok(Engine {
+      #[cfg(feature = "threads")]
+      pool: match self.pool {
+        Some(pool) => pool,
+        None => Arc::new(
+          ThreadPoolBuilder::new()
+            .num_threads(self.threads.unwrap_or(0))
+            .build()?,
+        ),
+      },
+      time_limit: self.time_limit,
+      settings: self.settings,
+    })
+  }
+}
+impl Default for EngineBuilder {
+  fn default() -> Self {
+    EngineBuilder {
+      threads: None,
+      #[cfg(feature = "threads")]
+      pool: None,
+      time_limit: Duration::from_secs(1),
+      settings: Settings::default(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+
+  #[test]
+  #[cfg(feature = "threads")]
+  fn test_engines_with_different_thread_counts() {
+    let single = Engine::builder()
+      .threads(1)
+      .time_limit(Duration::from_millis(100))
+      .build()
+      .unwrap();
+    let double = Engine::builder()
+      .threads(2)
+      .time_limit(Duration::from_millis(100))
+      .build()
+      .unwrap();
+
+    assert_eq!(single.threads(), 1);
+    assert_eq!(double.threads(), 2);
+
+    let mut board = Board::from_str(
+      "---------
+---------
+---x-----
+---xoo---
+----xo---
+---xxxo--
+------oo-
+--------x
+---------",
+    )
+    .unwrap();
+
+    let outcome = single.decide(&mut board, Player::O).unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    assert_eq!(*board.get_tile(move_.tile), Some(Player::O));
+  }
+
+  #[test]
+  #[cfg(feature = "threads")]
+  fn test_shared_thread_pool() {
+    let pool = Arc::new(ThreadPoolBuilder::new().num_threads(3).build().unwrap());
+
+    let engines = [1, 2].map(|threads| {
+      Engine::builder()
+        .threads(threads)
+        .thread_pool(pool.clone())
+        .time_limit(Duration::from_millis(50))
+        .build()
+        .unwrap()
+    });
+
+    for engine in &engines {
+      assert_eq!(engine.threads(), 3);
+
+      let mut board = Board::new_empty(9).unwrap();
+      assert!(engine.decide(&mut board, Player::X).unwrap().best.is_some());
+    }
+  }
+
+  #[test]
+  fn test_progress_events() {
+    use std::sync::Mutex;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let engine = {
+      let events = events.clone();
+      Engine::builder()
+        .threads(1)
+        .progress(move |event| events.lock().unwrap().push(event))
+        .build()
+        .unwrap()
+    };
+
+    let mut board = Board::from_str(
+      "xxxx-----
+---------
+---------
+---------
+----o----
+---o-----
+--o------
+---------
+---------",
+    )
+    .unwrap();
+
+    engine.decide_to_depth(&mut board, Player::X, 2).unwrap();
+
+    let events = events.lock().unwrap();
+
+    assert!(matches!(events[0], SearchEvent::Depth { depth: 1, .. }));
+    assert!(events.contains(&SearchEvent::WinFound));
+    assert!(events.iter().any(|event| matches!(
+      event,
+      SearchEvent::Iteration { depth: 1, best_move, stats }
+        if best_move.tile == TilePointer { x: 4, y: 0 } && stats.depth_reached == 1
+    )));
+    assert!(matches!(
+      events.last(),
+      Some(SearchEvent::Finished { depth: 1, best_line }) if best_line[0] == TilePointer { x: 4, y: 0 }
+    ));
+    assert!(!events
+      .iter()
+      .any(|event| matches!(event, SearchEvent::Tree { .. })));
+  }
+
+  #[test]
+  fn test_stop_when() {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---xxx---
+---------
+----oo---
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let completed = Arc::new(AtomicU8::new(0));
+
+    let engine = {
+      let progress = completed.clone();
+      let stop = completed.clone();
+
+      Engine::builder()
+        .time_limit(Duration::from_secs(30))
+        .progress(move |event| {
+          if let SearchEvent::Iteration { depth, .. } = event {
+            progress.store(depth, Ordering::SeqCst);
+          }
+        })
+        .stop_when(move || stop.load(Ordering::SeqCst) >= 2)
+        .build()
+        .unwrap()
+    };
+
+    let outcome = engine.decide(&mut board.clone(), Player::O).unwrap();
+    let expected = engine
+      .decide_to_depth(&mut board.clone(), Player::O, 2)
+      .unwrap();
+
+    assert_eq!(outcome.stats.depth_reached, 2);
+    assert_eq!(outcome.best, expected.best);
+  }
+
+  #[test]
+  fn test_dump_tree() {
+    use std::sync::Mutex;
+
+    let dot = Arc::new(Mutex::new(None));
+
+    let engine = {
+      let dot = dot.clone();
+      Engine::builder()
+        .threads(1)
+        .dump_tree(1)
+        .progress(move |event| {
+          if let SearchEvent::Tree { dot: tree } = event {
+            *dot.lock().unwrap() = Some(tree);
+          }
+        })
+        .build()
+        .unwrap()
+    };
+
+    let mut board = Board::new_empty(9).unwrap();
+    board.set_tile(TilePointer { x: 4, y: 4 }, Some(Player::X));
+
+    engine.decide_to_depth(&mut board, Player::O, 3).unwrap();
+
+    let dot = dot.lock().unwrap().take().expect("tree was dumped");
+
+    assert!(dot.starts_with("digraph tree {"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("root -> n0;"));
+
+    // top-level moves and one level of replies, but no deeper
+    assert!(dot.contains("depth 3\""));
+    assert!(dot.contains("depth 2\""));
+    assert!(!dot.contains("depth 1\""));
+  }
+
+  #[test]
+  fn test_default_search_config() {
+    let config = SearchConfig::default();
+
+    // the beams used before they were configurable
+    for count in [1, 10, 48, 60, 200] {
+      for depth in 2..=12 {
+        let expected = match depth {
+          2 => (count / 2).max(24),
+          3 => 16,
+          4..=7 => 8,
+          8 => 4,
+          _ => 2,
+        };
+
+        assert_eq!(config.replies_kept(depth, count), expected);
+      }
+    }
+
+    let all = SearchConfig {
+      depth_beams: Vec::new(),
+      ..SearchConfig::default()
+    };
+    assert_eq!(all.replies_kept(5, 60), 60);
+  }
+
+  #[test]
+  fn test_short_time_limit() {
+    let engine = Engine::builder()
+      .time_limit(Duration::from_millis(5))
+      .build()
+      .unwrap();
+
+    let mut board = Board::new_empty(15).unwrap();
+    board.set_tile(TilePointer { x: 7, y: 7 }, Some(Player::X));
+
+    let start = std::time::Instant::now();
+    let outcome = engine.decide(&mut board, Player::O).unwrap();
+
+    // only the first depth, which always completes
+    assert_eq!(outcome.stats.depth_reached, 1);
+    assert!(start.elapsed() < Duration::from_millis(500));
+  }
+
+  #[test]
+  fn test_narrow_search_config() {
+    let engine = Engine::builder()
+      .threads(1)
+      .search_config(SearchConfig {
+        top_level_beam: 0.5,
+        depth_beams: vec![4, 1],
+        ..SearchConfig::default()
+      })
+      .build()
+      .unwrap();
+
+    let mut board = Board::from_str(
+      "---------
+-xoooo---
+---------
+---------
+---------
+---------
+--xx-----
+---------
+---------",
+    )
+    .unwrap();
+
+    let outcome = engine.decide_to_depth(&mut board, Player::X, 3).unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    // even the narrowest search sees the immediate threat
+    assert_eq!(move_.tile, TilePointer { x: 6, y: 1 });
+  }
+
+  #[test]
+  fn test_wider_beams_search_more() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---x-----
+----xo---
+-----o---
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let nodes_evaluated = |depth_beams: Vec<usize>| {
+      let engine = Engine::builder()
+        .threads(1)
+        .search_config(SearchConfig {
+          depth_beams,
+          ..SearchConfig::default()
+        })
+        .build()
+        .unwrap();
+
+      let outcome = engine
+        .decide_to_depth(&mut board.clone(), Player::X, 3)
+        .unwrap();
+      assert_eq!(outcome.stats.depth_reached, 3);
+
+      outcome.stats.nodes_evaluated
+    };
+
+    let narrow = nodes_evaluated(vec![2]);
+    let wide = nodes_evaluated(vec![60]);
+
+    assert!(narrow < wide, "{narrow} {wide}");
+
+    // depths past the end of the schedule use its last entry
+    let config = SearchConfig {
+      depth_beams: vec![30, 5],
+      ..SearchConfig::default()
+    };
+
+    assert_eq!(config.replies_kept(2, 80), 40);
+    assert_eq!(config.replies_kept(3, 80), 5);
+    assert_eq!(config.replies_kept(12, 80), 5);
+  }
+
+  #[test]
+  fn test_killer_moves_search_less() {
+    // X's moves making a four leave O with one block, and the five refutes
+    // every other reply, while reading order reaches it only late
+    let board = Board::from_str(
+      "---------
+-----o---
+---xxxo--
+---------
+----o----
+---------
+---------
+-oxxx----
+---------",
+    )
+    .unwrap();
+
+    let search = |killer_moves| {
+      let engine = Engine::builder()
+        .threads(1)
+        .search_config(SearchConfig {
+          top_level_beam: 1.0,
+          killer_moves,
+          ..SearchConfig::default()
+        })
+        .build()
+        .unwrap();
+
+      engine
+        .decide_to_depth(&mut board.clone(), Player::X, 3)
+        .unwrap()
+    };
+
+    let with = search(true);
+    let without = search(false);
+
+    assert_eq!(with.best, without.best);
+    assert_eq!(with.pv, without.pv);
+    assert_eq!(with.verdict, without.verdict);
+    assert!(
+      with.stats.nodes_evaluated < without.stats.nodes_evaluated,
+      "{} {}",
+      with.stats.nodes_evaluated,
+      without.stats.nodes_evaluated
+    );
+  }
+
+  #[test]
+  fn test_tie_break_is_deterministic() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+----x----
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let decide = |threads, tie_break| {
+      let engine = Engine::builder()
+        .threads(threads)
+        .tie_break(tie_break)
+        .build()
+        .unwrap();
+
+      let outcome = engine
+        .decide_to_depth(&mut board.clone(), Player::O, 2)
+        .unwrap();
+      let move_ = outcome.best_move().unwrap();
+
+      move_.tile
+    };
+
+    for tie_break in [
+      TieBreak::FirstStable,
+      TieBreak::CenterPreferred,
+      TieBreak::Random(42),
+    ] {
+      assert_eq!(decide(1, tie_break), decide(4, tie_break));
+    }
+  }
+
+  #[test]
+  fn test_beginner_blocks_open_three() {
+    // two ends to block, so unlike a four this isn't answered by the forced
+    // block shortcut and the move goes through the skill's choice
+    let board = Board::from_str(
+      "---------
+---------
+---------
+--ooo----
+---------
+----x----
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let blocks = [TilePointer { x: 1, y: 3 }, TilePointer { x: 5, y: 3 }];
+
+    for seed in 0..10 {
+      let engine = Engine::builder()
+        .threads(1)
+        .skill(Skill::Beginner)
+        .skill_seed(seed)
+        .build()
+        .unwrap();
+
+      let outcome = engine
+        .decide_to_depth(&mut board.clone(), Player::X, 4)
+        .unwrap();
+      let move_ = outcome.best_move().unwrap();
+
+      assert_ne!(outcome.verdict, Verdict::Forced);
+      assert!(blocks.contains(&move_.tile));
+    }
+  }
+
+  #[test]
+  #[cfg(feature = "threads")]
+  fn test_cancel_async_search() {
+    use std::time::Instant;
+
+    let board = Board::from_str(
+      "---------
+---------
+---x-----
+---xoo---
+----xo---
+---xxxo--
+------oo-
+--------x
+---------",
+    )
+    .unwrap();
+
+    let engine = Engine::builder()
+      .threads(1)
+      .time_limit(Duration::from_secs(30))
+      .build()
+      .unwrap();
+
+    let start = Instant::now();
+    let handle = engine.decide_async(&board, Player::O);
+
+    thread::sleep(Duration::from_millis(50));
+    handle.cancel();
+
+    let outcome = handle.join().unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(*board.get_tile(move_.tile), None);
+  }
+
+  #[test]
+  #[cfg(feature = "threads")]
+  fn test_ponder() {
+    use std::time::Instant;
+
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+-oxxx----
+---------
+----o----
+---------
+---------",
+    )
+    .unwrap();
+
+    let engine = Engine::builder()
+      .threads(1)
+      .time_limit(Duration::from_secs(5))
+      .build()
+      .unwrap();
+
+    // hit, reuses the pondering without waiting for the time limit
+    let ponder = engine
+      .ponder(&board, Player::O, TilePointer { x: 5, y: 4 })
+      .unwrap();
+    assert_eq!(ponder.predicted_move(), TilePointer { x: 5, y: 4 });
+
+    let mut hit = board.clone();
+    hit.set_tile(TilePointer { x: 5, y: 4 }, Some(Player::X));
+    assert!(ponder.is_hit(&hit, Player::O));
+
+    thread::sleep(Duration::from_millis(300));
+
+    let start = Instant::now();
+    let outcome = engine.decide_pondered(&mut hit, Player::O, ponder).unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(move_.tile, TilePointer { x: 6, y: 4 });
+    assert_eq!(*hit.get_tile(move_.tile), Some(Player::O));
+
+    // miss, searches from scratch
+    let ponder = engine
+      .ponder(&board, Player::O, TilePointer { x: 5, y: 4 })
+      .unwrap();
+
+    let mut miss = board.clone();
+    miss.set_tile(TilePointer { x: 6, y: 4 }, Some(Player::X));
+    assert!(!ponder.is_hit(&miss, Player::O));
+
+    let outcome = engine
+      .decide_pondered(&mut miss, Player::O, ponder)
+      .unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    assert_eq!(move_.tile, TilePointer { x: 5, y: 4 });
+
+    // predictions that can't be played
+    let occupied = TilePointer { x: 2, y: 4 };
+    assert!(matches!(
+      engine.ponder(&board, Player::O, occupied),
+      Err(GomokuError::IllegalMove(PlaceError::Occupied(tile))) if tile == occupied
+    ));
+
+    let outside = TilePointer { x: 9, y: 4 };
+    assert!(matches!(
+      engine.ponder(&board, Player::O, outside),
+      Err(GomokuError::IllegalMove(PlaceError::OutOfBounds(tile))) if tile == outside
+    ));
+  }
+
+  #[test]
+  #[cfg(feature = "threads")]
+  fn test_drop_ponder_handle() {
+    use std::sync::{
+      atomic::{AtomicBool, Ordering},
+      mpsc,
+    };
+
+    let finished = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    let progress = finished.clone();
+    let engine = Engine::builder()
+      .threads(1)
+      .time_limit(Duration::from_secs(100))
+      .progress(move |event| {
+        if let SearchEvent::Finished { .. } = event {
+          progress.store(true, Ordering::SeqCst);
+          sender.send(()).ok();
+        }
+      })
+      .build()
+      .unwrap();
+
+    let board = Board::new_empty(9).unwrap();
+    let ponder = engine
+      .ponder(&board, Player::O, TilePointer { x: 4, y: 4 })
+      .unwrap();
+
+    thread::sleep(Duration::from_millis(50));
+    assert!(!finished.load(Ordering::SeqCst));
+
+    // pondering would run for over a quarter of an hour, only the drop stops it
+    drop(ponder);
+
+    receiver
+      .recv_timeout(Duration::from_secs(10))
+      .expect("dropped pondering kept running");
+  }
+
+  #[test]
+  #[cfg(feature = "threads")]
+  fn test_ponder_stops_on_its_own() {
+    use std::sync::mpsc;
+
+    let (sender, receiver) = mpsc::channel();
+
+    let engine = Engine::builder()
+      .threads(1)
+      .time_limit(Duration::from_millis(20))
+      .progress(move |event| {
+        if let SearchEvent::Finished { .. } = event {
+          sender.send(()).ok();
+        }
+      })
+      .build()
+      .unwrap();
+
+    let board = Board::new_empty(15).unwrap();
+    let _ponder = engine
+      .ponder(&board, Player::O, TilePointer { x: 7, y: 7 })
+      .unwrap();
+
+    // bounded by ten time limits even though the handle is kept
+    receiver
+      .recv_timeout(Duration::from_secs(10))
+      .expect("pondering kept running past its bound");
+  }
+
+  #[test]
+  fn test_heuristic_changes_move() {
+    // O has an open four on the second row, X can either block it or extend
+    // its own open two on the seventh row to an open three
+    let board = Board::from_str(
+      "---------
+--oooo---
+---------
+---------
+---------
+---------
+--xx-----
+---------
+---------",
+    )
+    .unwrap();
+
+    let decide = |heuristic| {
+      let engine = Engine::builder()
+        .threads(1)
+        .heuristic(heuristic)
+        .build()
+        .unwrap();
+
+      let outcome = engine
+        .decide_to_depth(&mut board.clone(), Player::X, 1)
+        .unwrap();
+      let move_ = outcome.best_move().unwrap();
+
+      move_.tile
+    };
+
+    let blocks = [TilePointer { x: 1, y: 1 }, TilePointer { x: 6, y: 1 }];
+    let extends = [TilePointer { x: 1, y: 6 }, TilePointer { x: 4, y: 6 }];
+
+    assert!(blocks.contains(&decide(Heuristic::default())));
+
+    let aggressive = Heuristic {
+      open_three: 2 * Heuristic::DEFAULT.open_three,
+      ..Heuristic::DEFAULT
+    };
+
+    assert!(extends.contains(&decide(aggressive)));
+  }
+
+  #[test]
+  fn test_decide_among() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+--xxx----
+---------
+---oo----
+---------
+---------",
+    )
+    .unwrap();
+    let engine = Engine::builder().build().unwrap();
+    let time_limit = Duration::from_millis(100);
+
+    let candidates = [TilePointer { x: 0, y: 0 }, TilePointer { x: 8, y: 8 }];
+    let mut played = board.clone();
+    let outcome = engine
+      .decide_among(&mut played, Player::X, &candidates, time_limit)
+      .unwrap();
+
+    let best = outcome.best_move().unwrap().tile;
+    assert!(candidates.contains(&best));
+    assert_eq!(played[best], Some(Player::X));
+
+    let occupied = TilePointer { x: 2, y: 4 };
+    assert!(matches!(
+      engine.decide_among(&mut board.clone(), Player::X, &[occupied], time_limit),
+      Err(GomokuError::IllegalMove(PlaceError::Occupied(tile))) if tile == occupied
+    ));
+
+    let outside = TilePointer { x: 9, y: 0 };
+    assert!(matches!(
+      engine.decide_among(&mut board.clone(), Player::X, &[outside], time_limit),
+      Err(GomokuError::IllegalMove(PlaceError::OutOfBounds(_)))
+    ));
+  }
+
+  #[test]
+  fn test_opening_move() {
+    let engine = Engine::builder()
+      .time_limit(Duration::from_millis(50))
+      .build()
+      .unwrap();
+
+    let mut board = Board::new_empty(16).unwrap();
+    let outcome = engine.opening_move(&mut board, Player::X).unwrap();
+
+    assert_eq!(
+      outcome.best_move().unwrap().tile,
+      TilePointer { x: 8, y: 8 }
+    );
+    assert_eq!(outcome.verdict, Verdict::Unclear);
+    assert_eq!(outcome.stats.depth_reached, 0);
+    assert_eq!(board[TilePointer { x: 8, y: 8 }], Some(Player::X));
+
+    let outcome = engine.opening_move(&mut board, Player::O).unwrap();
+    let reply = outcome.best_move().unwrap().tile;
+
+    assert!(outcome.stats.depth_reached > 0);
+    assert_eq!(board[reply], Some(Player::O));
+  }
+
+  #[test]
+  fn test_overline_under_renju() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+-xxxxxx--
+---------
+----o----
+---------
+---------",
+    )
+    .unwrap();
+
+    let freestyle = Engine::builder().build().unwrap();
+    assert!(matches!(
+      freestyle.decide(&mut board.clone(), Player::O),
+      Err(GomokuError::GameEnd { winner: Player::X })
+    ));
+
+    // the overline doesn't win for X, so the game goes on
+    let renju = Engine::builder().rules(Rules::renju()).build().unwrap();
+    let mut renju_board = board.clone();
+    let outcome = renju.decide(&mut renju_board, Player::O).unwrap();
+    let move_ = outcome.best_move().unwrap();
+    assert_eq!(*board.get_tile(move_.tile), None);
+  }
+
+  #[test]
+  fn test_renju_overline_four_is_not_a_win() {
+    // X at e5 makes fours that only an overline completes
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+x-xx-x-x-
+---------
+---------
+--o------
+---------",
+    )
+    .unwrap();
+
+    let decide = |rules| {
+      Engine::builder()
+        .threads(1)
+        .rules(rules)
+        .build()
+        .unwrap()
+        .decide_to_depth(&mut board.clone(), Player::X, 1)
+        .unwrap()
+    };
+
+    assert_eq!(decide(Rules::freestyle()).verdict, Verdict::Win);
+    assert_ne!(decide(Rules::renju()).verdict, Verdict::Win);
+  }
+
+  #[test]
+  fn test_no_legal_moves_under_renju() {
+    // stripes without a five anywhere, the only empty tile would make an
+    // overline for X
+    let tiles = (0..9)
+      .map(|y| {
+        (0..9)
+          .map(|x| match (y, x) {
+            (0, 3) => None,
+            (0, 0..=6) => Some(Player::X),
+            _ if (x / 2 + y) % 2 == 0 => Some(Player::X),
+            _ => Some(Player::O),
+          })
+          .collect()
+      })
+      .collect();
+    let board = Board::new(tiles).unwrap();
+    let tile = TilePointer { x: 3, y: 0 };
+
+    assert_eq!(board.winner(), None);
+    assert!(Rules::renju().is_forbidden(&board, tile, Player::X));
+
+    let renju = Engine::builder().rules(Rules::renju()).build().unwrap();
+    assert!(matches!(
+      renju.decide(&mut board.clone(), Player::X),
+      Err(GomokuError::NoLegalMoves { player: Player::X })
+    ));
+
+    // O can still play there, and so can X under freestyle rules
+    let outcome = renju.decide(&mut board.clone(), Player::O).unwrap();
+    assert_eq!(outcome.best_move().unwrap().tile, tile);
+
+    let freestyle = Engine::builder().build().unwrap();
+    let outcome = freestyle.decide(&mut board.clone(), Player::X).unwrap();
+    assert_eq!(outcome.best_move().unwrap().tile, tile);
+
+    // taking the last other tile leaves X without a move, which wins for O
+    let other = TilePointer { x: 8, y: 8 };
+    let mut board = board;
+    board.set_tile(other, None);
+
+    let outcome = renju.decide(&mut board, Player::O).unwrap();
+    assert_eq!(outcome.best_move().unwrap().tile, other);
+    assert_eq!(outcome.verdict, crate::Verdict::Win);
+  }
+
+  #[test]
+  fn test_rules_without_draws() {
+    // stripes with two empty tiles, no filling of them makes a five
+    let empty = [TilePointer { x: 2, y: 3 }, TilePointer { x: 7, y: 5 }];
+    let tiles = (0..9)
+      .map(|y| {
+        (0..9)
+          .map(|x| {
+            if empty.contains(&TilePointer { x, y }) {
+              None
+            } else if (x / 2 + y) % 2 == 0 {
+              Some(Player::X)
+            } else {
+              Some(Player::O)
+            }
+          })
+          .collect()
+      })
+      .collect();
+    let board = Board::new(tiles).unwrap();
+
+    let draws = Engine::builder().build().unwrap();
+    let no_draws = Engine::builder()
+      .rules(Rules::freestyle().with_draws(false))
+      .build()
+      .unwrap();
+
+    let outcome = draws.decide(&mut board.clone(), Player::X).unwrap();
+    assert_eq!(outcome.verdict, crate::Verdict::Draw);
+
+    // whatever X plays, O fills the board and X is left without a move
+    let outcome = no_draws.decide(&mut board.clone(), Player::X).unwrap();
+    assert_eq!(outcome.verdict, crate::Verdict::Loss);
+
+    let mut full = board;
+    for tile in empty {
+      full.set_tile(tile, Some(Player::X));
+    }
+    assert_eq!(full.winner(), None);
+
+    assert!(draws
+      .decide(&mut full.clone(), Player::O)
+      .unwrap()
+      .is_draw());
+    assert!(matches!(
+      no_draws.decide(&mut full, Player::O),
+      Err(GomokuError::NoLegalMoves { player: Player::O })
+    ));
+  }
+
+  #[test]
+  fn test_explain() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+xoooo----
+---------
+--x-x----
+---------
+---------",
+    )
+    .unwrap();
+    let engine = Engine::builder()
+      .time_limit(Duration::from_millis(100))
+      .build()
+      .unwrap();
+
+    let block = TilePointer { x: 5, y: 4 };
+    let explanation = engine.explain(&board, Player::X, block).unwrap();
+
+    assert!(explanation.blocks_opponent_four());
+    assert!(!explanation.creates_four());
+    assert_eq!(explanation.pv[0], block);
+    assert!(explanation.pv.len() > 1);
+
+    let extend = TilePointer { x: 3, y: 6 };
+    let explanation = engine.explain(&board, Player::X, extend).unwrap();
+
+    assert!(!explanation.blocks_opponent_four());
+    assert!(explanation.creates_open_three());
+    assert!(explanation.score < 0, "the four wins for O");
+
+    assert!(matches!(
+      engine.explain(&board, Player::X, TilePointer { x: 1, y: 4 }),
+      Err(GomokuError::IllegalMove(_))
+    ));
+  }
+
+  #[test]
+  fn test_center_bias() {
+    let board = Board::new_empty(9).unwrap();
+    let center = TilePointer { x: 4, y: 4 };
+
+    let distance = |center_bias| {
+      let engine = Engine::builder()
+        .threads(1)
+        .heuristic(Heuristic {
+          center_bias,
+          ..Heuristic::DEFAULT
+        })
+        .build()
+        .unwrap();
+
+      let outcome = engine
+        .decide_to_depth(&mut board.clone(), Player::X, 1)
+        .unwrap();
+      let move_ = outcome.best_move().unwrap();
+
+      board.squared_distance_from_center(move_.tile)
+    };
+
+    assert!(distance(1000) < distance(0));
+    assert_eq!(distance(1000), board.squared_distance_from_center(center));
+  }
+}
@@ -1,13 +1,35 @@
 use std::{error::Error, fmt::Display};
 
-use crate::board;
+use crate::{board, PlaceError, Player};
 
+/// Errors returned by the engine
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
 pub enum GomokuError {
+  /// There are no empty tiles left on the board
+  ///
+  /// No longer returned by the search, a full board is reported as
+  /// [`Verdict::Draw`](crate::Verdict::Draw) with no move to play.
   NoEmptyTiles,
-  GameEnd,
+  /// The game has already ended
+  GameEnd {
+    /// Player with five in a row
+    winner: Player,
+  },
+  /// The player on turn has no legal move and the game isn't a draw
+  ///
+  /// Happens under [`Renju`](crate::Variant::Renju) rules, when every empty
+  /// tile is forbidden for X, or on a full board if the rules don't allow
+  /// draws, see [`Rules::can_draw`](crate::Rules::can_draw). It means the
+  /// player has lost.
+  NoLegalMoves {
+    /// Player without a legal move
+    player: Player,
+  },
+  /// The board has invalid shape
   MisshapedBoard(board::Error),
+  /// The move can't be played
+  IllegalMove(PlaceError),
 }
 
 impl Error for GomokuError {}
@@ -16,8 +38,10 @@ impl Display for GomokuError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
       GomokuError::NoEmptyTiles => write!(f, "no empty tiles left"),
-      GomokuError::GameEnd => write!(f, "game already ended"),
+      GomokuError::GameEnd { winner } => write!(f, "game already ended, {winner} won"),
+      GomokuError::NoLegalMoves { player } => write!(f, "{player} has no legal moves left"),
       GomokuError::MisshapedBoard(error) => write!(f, "{error}"),
+      GomokuError::IllegalMove(error) => write!(f, "{error}"),
     }
   }
 }
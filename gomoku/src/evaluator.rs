@@ -0,0 +1,108 @@
+use crate::{Board, Heuristic, Player, Score, State};
+
+/// Static evaluation of a position
+///
+/// Implement this to replace the built-in evaluation, e.g. with a trained
+/// model. Custom evaluators see the whole board for every searched move, so
+/// they are considerably slower than the built-in incremental evaluation.
+pub trait Evaluator {
+  /// Evaluate the board from the perspective of `player`.
+  ///
+  /// Higher scores are better for `player`. The state should be
+  /// [`State::Win`] if `player` has already won.
+  fn evaluate(&self, board: &Board, player: Player) -> (Score, State);
+}
+
+/// Built-in evaluation based on scoring shapes in every line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct DefaultEvaluator {
+  /// Shape scores to evaluate with
+  pub heuristic: Heuristic,
+}
+impl DefaultEvaluator {
+  /// Create an evaluator using the given shape scores.
+  pub fn new(heuristic: Heuristic) -> Self {
+    DefaultEvaluator { heuristic }
+  }
+}
+impl Evaluator for DefaultEvaluator {
+  fn evaluate(&self, board: &Board, player: Player) -> (Score, State) {
+    board.evaluate_for_with(player, &self.heuristic)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+  use crate::{Engine, TilePointer};
+
+  /// Evaluator that only cares about taking the top left corner
+  struct Corner;
+  impl Evaluator for Corner {
+    fn evaluate(&self, board: &Board, player: Player) -> (Score, State) {
+      let corner = TilePointer { x: 0, y: 0 };
+
+      if *board.get_tile(corner) == Some(player) {
+        (1, State::Win)
+      } else {
+        (0, State::NotEnd)
+      }
+    }
+  }
+
+  #[test]
+  fn test_custom_evaluator() {
+    let board = Board::new_empty(9).unwrap();
+
+    let engine = Engine::builder()
+      .threads(1)
+      .evaluator(Box::new(Corner))
+      .build()
+      .unwrap();
+
+    let outcome = engine
+      .decide_to_depth(&mut board.clone(), Player::X, 1)
+      .unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    assert_eq!(move_.tile, TilePointer { x: 0, y: 0 });
+  }
+
+  #[test]
+  fn test_default_evaluator_matches_builtin() {
+    let board = Board::from_str(
+      "---------
+--oooo---
+---------
+---------
+---------
+---------
+--xx-----
+---------
+---------",
+    )
+    .unwrap();
+
+    let builtin = Engine::builder().threads(1).build().unwrap();
+    let custom = Engine::builder()
+      .threads(1)
+      .evaluator(Box::new(DefaultEvaluator::default()))
+      .build()
+      .unwrap();
+
+    let outcome = builtin
+      .decide_to_depth(&mut board.clone(), Player::X, 2)
+      .unwrap();
+    let expected = outcome.best_move().unwrap();
+    let outcome = custom
+      .decide_to_depth(&mut board.clone(), Player::X, 2)
+      .unwrap();
+    let actual = outcome.best_move().unwrap();
+
+    assert_eq!(expected.tile, actual.tile);
+    assert_eq!(expected.score, actual.score);
+  }
+}
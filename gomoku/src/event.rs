@@ -0,0 +1,93 @@
+use std::{fmt, time::Duration};
+
+use crate::{Move, Stats, TilePointer};
+
+/// Progress report emitted during a search
+///
+/// Subscribe to these with [`EngineBuilder::progress`](crate::EngineBuilder::progress).
+/// The [`Display`](fmt::Display) implementation produces a human readable
+/// message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SearchEvent {
+  /// Started computing the next depth
+  Depth {
+    /// Depth being computed
+    depth: u8,
+    /// Number of nodes in the search tree
+    nodes: usize,
+    /// Time left, `None` if the search isn't time limited
+    remaining: Option<Duration>,
+  },
+  /// Finished computing a depth
+  Iteration {
+    /// Depth just completed
+    depth: u8,
+    /// Best move found so far
+    best_move: Move,
+    /// Stats of the search so far
+    stats: Stats,
+  },
+  /// One of the moves leads to a win
+  WinFound,
+  /// All moves lead to a loss
+  AllLosing,
+  /// All moves lead to a draw
+  AllDraws,
+  /// All moves except one lead to a loss or a draw
+  OneMoveLeft,
+  /// Dump of the search tree, only sent if enabled using
+  /// [`EngineBuilder::dump_tree`](crate::EngineBuilder::dump_tree)
+  Tree {
+    /// The tree as a Graphviz DOT graph
+    dot: String,
+  },
+  /// The search ended
+  Finished {
+    /// Last fully searched depth
+    depth: u8,
+    /// Best move followed by the expected replies
+    best_line: Vec<TilePointer>,
+  },
+}
+
+impl fmt::Display for SearchEvent {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SearchEvent::Depth {
+        depth,
+        nodes,
+        remaining,
+      } => {
+        write!(f, "computing depth {depth} for {nodes} nodes")?;
+
+        if let Some(remaining) = remaining {
+          write!(f, " ({remaining:?} remaining)")?;
+        }
+
+        Ok(())
+      },
+      SearchEvent::Iteration {
+        depth, best_move, ..
+      } => write!(
+        f,
+        "depth {depth} done, best move {} with score {}",
+        best_move.tile, best_move.score
+      ),
+      SearchEvent::WinFound => write!(f, "Winning move found!"),
+      SearchEvent::AllLosing => write!(f, "All moves are losing :("),
+      SearchEvent::AllDraws => write!(f, "All moves are draws."),
+      SearchEvent::OneMoveLeft => write!(f, "Only one viable move left"),
+      SearchEvent::Tree { dot } => write!(f, "Search tree:\n{dot}"),
+      SearchEvent::Finished { depth, best_line } => {
+        write!(f, "Searched to depth {depth}! Best move sequence:")?;
+
+        for tile in best_line {
+          write!(f, " {tile}")?;
+        }
+
+        Ok(())
+      },
+    }
+  }
+}
@@ -0,0 +1,81 @@
+use crate::{Board, DoubleThreat, Player, Score, Threat, Threats, TilePointer};
+
+/// Reasons for playing a move, see [`Engine::explain`](crate::Engine::explain)
+///
+/// Meant to be rendered by a frontend, so it only collects the facts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Explanation {
+  /// The explained move
+  pub tile: TilePointer,
+  /// Threats of the player including the new stone
+  pub created: Threats,
+  /// Threats of the opponent the new stone stands in the way of
+  pub blocked: Threats,
+  /// Double threat made by the move, if any
+  pub double_threat: Option<DoubleThreat>,
+  /// The move followed by the expected replies
+  pub pv: Vec<TilePointer>,
+  /// Score of the position after the move from the player's perspective
+  pub score: Score,
+}
+impl Explanation {
+  /// Collect the threats made and blocked by `player` playing at the empty
+  /// `tile` of `board`.
+  pub(crate) fn new(board: &Board, player: Player, tile: TilePointer, score: Score) -> Self {
+    let mut after = board.clone();
+    after.set_tile(tile, Some(player));
+
+    Explanation {
+      tile,
+      created: retain(after.threats(player), |threat| {
+        threat.stones.contains(&tile)
+      }),
+      blocked: retain(board.threats(!player), |threat| {
+        threat.completions.contains(&tile)
+      }),
+      double_threat: board.double_threat(tile, player),
+      pv: vec![tile],
+      score,
+    }
+  }
+
+  /// Check if the move completes a five.
+  pub fn wins(&self) -> bool {
+    !self.created.fives.is_empty()
+  }
+
+  /// Check if the move makes a four, open or not.
+  pub fn creates_four(&self) -> bool {
+    !self.created.fours.is_empty() || !self.created.open_fours.is_empty()
+  }
+
+  /// Check if the move makes an open three.
+  pub fn creates_open_three(&self) -> bool {
+    !self.created.open_threes.is_empty()
+  }
+
+  /// Check if the move takes a completion of the opponent's four.
+  pub fn blocks_opponent_four(&self) -> bool {
+    !self.blocked.fours.is_empty() || !self.blocked.open_fours.is_empty()
+  }
+
+  /// Check if the move takes a completion of the opponent's open three.
+  pub fn blocks_opponent_three(&self) -> bool {
+    !self.blocked.open_threes.is_empty()
+  }
+}
+
+/// Keep only the threats passing the filter.
+fn retain(mut threats: Threats, keep: impl Fn(&Threat) -> bool) -> Threats {
+  for list in [
+    &mut threats.open_threes,
+    &mut threats.fours,
+    &mut threats.open_fours,
+    &mut threats.fives,
+  ] {
+    list.retain(&keep);
+  }
+
+  threats
+}
@@ -0,0 +1,407 @@
+use crate::{board, Board, PlaceError, Player, Rules, TilePointer};
+
+/// Status of a game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+  /// The game goes on
+  InProgress,
+  /// The player has five in a row
+  Win(Player),
+  /// The board is full and nobody won
+  ///
+  /// Stones are never removed, so no position can repeat and every game
+  /// reaches a win or this after at most as many moves as there are tiles.
+  /// A loop playing until the game is no longer
+  /// [`InProgress`](Status::InProgress) always terminates.
+  Draw,
+}
+
+/// A game in progress
+///
+/// Owns the board and keeps track of whose turn it is and of the moves
+/// played. Played by [`Rules::freestyle`] unless set with
+/// [`GameState::with_rules`].
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct GameState {
+  board: Board,
+  to_move: Player,
+  history: Vec<TilePointer>,
+  status: Status,
+  rules: Rules,
+}
+impl GameState {
+  /// Start a game on an empty board of the given size. X moves first.
+  ///
+  /// # Errors
+  /// Returns an error if the size is below [`Board::MIN_SIZE`].
+  pub fn new(size: u8) -> Result<Self, board::Error> {
+    Ok(Self::from_board(Board::new_empty(size)?, Player::X))
+  }
+
+  /// Continue a game from an existing position with `to_move` on turn.
+  ///
+  /// Stones already on the board are not part of the history, so they can't
+  /// be undone.
+  pub fn from_board(board: Board, to_move: Player) -> Self {
+    let rules = Rules::default();
+    let status = Self::compute_status(&board, rules);
+
+    GameState {
+      board,
+      to_move,
+      history: Vec::new(),
+      status,
+      rules,
+    }
+  }
+
+  /// Play the game by the given rules.
+  ///
+  /// Moves the rules forbid can't be played and only fives the rules count
+  /// win.
+  #[must_use]
+  pub fn with_rules(mut self, rules: Rules) -> Self {
+    self.rules = rules;
+    self.status = Self::compute_status(&self.board, self.rules);
+    self
+  }
+
+  /// Load a game from a FEN string, optionally with the moves that led to it.
+  ///
+  /// There are two loading modes:
+  /// - position-only: any form accepted by [`Board::from_fen`]. The stones
+  ///   are like in [`GameState::from_board`], so [`GameState::undo`] has
+  ///   nothing to take back and returns `None`.
+  /// - full-history: the extended form `size|data|side|ply|moves` with the
+  ///   last moves played, oldest first, as a comma separated list like
+  ///   `h8,i9,g7`. They must alternate and end with the opponent of `side`,
+  ///   and can be undone one by one. The list doesn't have to cover every
+  ///   stone, the rest stays fixed.
+  ///
+  /// See [`GameState::to_fen_with_history`] for the inverse.
+  ///
+  /// # Errors
+  /// Returns an error if the FEN is malformed, or the moves don't match the
+  /// stones on the board or would continue a finished game.
+  #[cfg(feature = "fen")]
+  pub fn from_fen_with_history(input: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let input = input.trim();
+
+    let (fen, moves) = match input.rsplit_once('|') {
+      Some((fen, moves)) if input.matches('|').count() == 4 => (fen, Some(moves)),
+      _ => (input, None),
+    };
+
+    let (mut board, to_move, ply) = crate::utils::parse_fen_with_state(fen)?;
+
+    let Some(moves) = moves else {
+      return Ok(Self::from_board(board, to_move));
+    };
+
+    let history = moves
+      .split(',')
+      .map(str::trim)
+      .filter(|tile| !tile.is_empty())
+      .map(TilePointer::try_from)
+      .collect::<Result<Vec<_>, _>>()?;
+
+    if history.len() > ply as usize {
+      return Err(format!("{} moves in history, but ply is {ply}", history.len()).into());
+    }
+
+    // take the moves back, last one first, to get the starting position
+    let mut player = to_move;
+    for &ptr in history.iter().rev() {
+      player = !player;
+
+      if board.get(ptr) != Some(&Some(player)) {
+        return Err(format!("move {ptr} doesn't match a stone of {player}").into());
+      }
+
+      board.set_tile(ptr, None);
+    }
+
+    let mut game = Self::from_board(board, player);
+    for ptr in history {
+      game.play(ptr)?;
+    }
+
+    Ok(game)
+  }
+
+  /// Convert the game to the extended FEN string including its history.
+  ///
+  /// Inverse of [`GameState::from_fen_with_history`].
+  #[cfg(feature = "fen")]
+  pub fn to_fen_with_history(&self) -> String {
+    let moves = self
+      .history
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<_>>()
+      .join(",");
+
+    let ply = (self.board.stone_count(Player::X) + self.board.stone_count(Player::O)) as u32;
+
+    format!(
+      "{}|{moves}",
+      crate::utils::to_fen_with_state(&self.board, self.to_move, ply)
+    )
+  }
+
+  /// Play a stone of the player on turn.
+  ///
+  /// # Errors
+  /// Returns an error if the game already ended, the tile is out of bounds,
+  /// is already occupied or the rules forbid it.
+  pub fn play(&mut self, ptr: TilePointer) -> Result<Status, PlaceError> {
+    if self.status != Status::InProgress {
+      return Err(PlaceError::GameOver);
+    }
+
+    if self.board.get(ptr) == Some(&None) && self.rules.is_forbidden(&self.board, ptr, self.to_move)
+    {
+      return Err(PlaceError::Forbidden(ptr));
+    }
+
+    self.board.place(ptr, self.to_move)?;
+
+    self.history.push(ptr);
+    self.to_move = !self.to_move;
+    self.status = Self::compute_status(&self.board, self.rules);
+
+    Ok(self.status)
+  }
+
+  /// Take back the last move and return it.
+  ///
+  /// Returns `None` if there are no moves to take back.
+  pub fn undo(&mut self) -> Option<TilePointer> {
+    let ptr = self.history.pop()?;
+
+    self.board.set_tile(ptr, None);
+    self.to_move = !self.to_move;
+    self.status = Self::compute_status(&self.board, self.rules);
+
+    Some(ptr)
+  }
+
+  /// Get iterator over all moves the player on turn can play, the empty
+  /// tiles the rules don't forbid.
+  ///
+  /// Empty once the game has ended.
+  pub fn legal_moves(&self) -> impl Iterator<Item = TilePointer> + '_ {
+    let in_progress = self.status == Status::InProgress;
+
+    self
+      .board
+      .legal_moves_iter(self.to_move, &self.rules)
+      .filter(move |_| in_progress)
+  }
+
+  /// Get the status of the game.
+  pub fn status(&self) -> Status {
+    self.status
+  }
+
+  /// Get the rules the game is played by.
+  pub fn rules(&self) -> Rules {
+    self.rules
+  }
+
+  /// Get the board.
+  pub fn board(&self) -> &Board {
+    &self.board
+  }
+
+  /// Get the player on turn.
+  pub fn to_move(&self) -> Player {
+    self.to_move
+  }
+
+  /// Get the moves played so far, oldest first.
+  pub fn history(&self) -> &[TilePointer] {
+    &self.history
+  }
+
+  /// Get the number of moves played so far.
+  pub fn ply(&self) -> usize {
+    self.history.len()
+  }
+
+  fn compute_status(board: &Board, rules: Rules) -> Status {
+    if let Some((player, _)) = board.winner_with(&rules) {
+      Status::Win(player)
+    } else if board.pointers_to_empty_tiles().next().is_none() {
+      Status::Draw
+    } else {
+      Status::InProgress
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+  use crate::Engine;
+
+  #[test]
+  fn test_play_and_undo() {
+    let mut game = GameState::new(9).unwrap();
+
+    assert_eq!(game.to_move(), Player::X);
+
+    for x in 0..4 {
+      game.play(TilePointer { x, y: 0 }).unwrap();
+      game.play(TilePointer { x, y: 1 }).unwrap();
+    }
+
+    assert_eq!(game.ply(), 8);
+    assert_eq!(game.legal_moves().count(), 81 - 8);
+
+    let occupied = TilePointer { x: 0, y: 0 };
+    assert_eq!(game.play(occupied), Err(PlaceError::Occupied(occupied)));
+    assert_eq!(game.to_move(), Player::X);
+
+    let winning = TilePointer { x: 4, y: 0 };
+    assert_eq!(game.play(winning), Ok(Status::Win(Player::X)));
+    assert_eq!(game.legal_moves().count(), 0);
+    assert_eq!(
+      game.play(TilePointer { x: 8, y: 8 }),
+      Err(PlaceError::GameOver)
+    );
+
+    assert_eq!(game.undo(), Some(winning));
+    assert_eq!(game.status(), Status::InProgress);
+    assert_eq!(game.to_move(), Player::X);
+    assert_eq!(*game.board().get_tile(winning), None);
+
+    while game.undo().is_some() {}
+
+    assert_eq!(game.ply(), 0);
+    assert_eq!(game.to_move(), Player::X);
+    assert_eq!(game.board(), &Board::new_empty(9).unwrap());
+  }
+
+  #[test]
+  fn test_renju_game() {
+    let mut game = GameState::from_board(
+      Board::from_str(
+        "---------
+---------
+---------
+---------
+-xxxx-x--
+---------
+---------
+--x------
+---x-----",
+      )
+      .unwrap(),
+      Player::X,
+    )
+    .with_rules(Rules::renju());
+
+    // an overline doesn't win for X under Renju, so it's forbidden
+    let overline = TilePointer { x: 5, y: 4 };
+    assert!(game.legal_moves().all(|tile| tile != overline));
+    assert_eq!(game.play(overline), Err(PlaceError::Forbidden(overline)));
+    assert_eq!(game.ply(), 0);
+
+    // it still wins under freestyle
+    let mut freestyle = game.clone().with_rules(Rules::freestyle());
+    assert_eq!(freestyle.play(overline), Ok(Status::Win(Player::X)));
+
+    // a finished overline isn't a win under Renju
+    let board = freestyle.board().clone();
+    assert_eq!(
+      GameState::from_board(board.clone(), Player::O).status(),
+      Status::Win(Player::X)
+    );
+    assert_eq!(
+      GameState::from_board(board, Player::O)
+        .with_rules(Rules::renju())
+        .status(),
+      Status::InProgress
+    );
+  }
+
+  #[cfg(feature = "fen")]
+  #[test]
+  fn test_from_fen_with_history() {
+    let mut game = GameState::from_fen_with_history("9|x/1o/2x//////|o|3|a1,b2,c3").unwrap();
+
+    assert_eq!(game.to_move(), Player::O);
+    assert_eq!(game.history().len(), 3);
+    assert_eq!(
+      GameState::from_fen_with_history(&game.to_fen_with_history())
+        .unwrap()
+        .history(),
+      game.history()
+    );
+
+    assert_eq!(game.undo(), Some(TilePointer { x: 2, y: 2 }));
+    assert_eq!(game.undo(), Some(TilePointer { x: 1, y: 1 }));
+    assert_eq!(game.undo(), Some(TilePointer { x: 0, y: 0 }));
+    assert_eq!(game.undo(), None);
+    assert_eq!(game.to_move(), Player::X);
+    assert_eq!(game.board(), &Board::new_empty(9).unwrap());
+
+    // only the position, the stones can't be taken back
+    let mut game = GameState::from_fen_with_history("9|x/1o/2x//////|o|3").unwrap();
+    assert_eq!(game.to_move(), Player::O);
+    assert_eq!(game.undo(), None);
+    assert_eq!(game.board().stone_count(Player::X), 2);
+
+    // wrong stone, wrong order and too many moves for the ply
+    assert!(GameState::from_fen_with_history("9|x/1o/2x//////|o|3|a1,b2,d4").is_err());
+    assert!(GameState::from_fen_with_history("9|x/1o/2x//////|o|3|b2,a1,c3").is_err());
+    assert!(GameState::from_fen_with_history("9|x/1o/2x//////|o|2|a1,b2,c3").is_err());
+  }
+
+  #[test]
+  fn test_self_play_reaches_draw() {
+    // stripes with runs of at most two stones in every direction, so no
+    // filling of the few empty tiles makes a five
+    let empty = [
+      TilePointer { x: 2, y: 3 },
+      TilePointer { x: 7, y: 5 },
+      TilePointer { x: 4, y: 8 },
+    ];
+    let tiles = (0..9)
+      .map(|y| {
+        (0..9)
+          .map(|x| {
+            if empty.contains(&TilePointer { x, y }) {
+              None
+            } else if (x / 2 + y) % 2 == 0 {
+              Some(Player::X)
+            } else {
+              Some(Player::O)
+            }
+          })
+          .collect()
+      })
+      .collect();
+
+    let mut game = GameState::from_board(Board::new(tiles).unwrap(), Player::X);
+    let engine = Engine::builder().build().unwrap();
+
+    while game.status() == Status::InProgress {
+      let outcome = engine
+        .decide_to_depth(&mut game.board().clone(), game.to_move(), 2)
+        .unwrap();
+      game.play(outcome.best_move().unwrap().tile).unwrap();
+    }
+
+    assert_eq!(game.status(), Status::Draw);
+    assert_eq!(game.ply(), empty.len());
+    assert!(engine
+      .decide(&mut game.board().clone(), game.to_move())
+      .unwrap()
+      .is_draw());
+  }
+}
@@ -0,0 +1,137 @@
+//! JSON export of analysis results for web frontends
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{
+  engine::Settings, outcome_from_nodes, search, utils::CancelToken, Board, GomokuError, Limit,
+  Move, Player, Score, Stats, Verdict,
+};
+
+/// Number of candidate moves included in [`analyze_json`]
+const CANDIDATES: usize = 5;
+
+/// Move with the tile in the `h8` form
+#[derive(Serialize)]
+struct JsonMove {
+  tile: String,
+  score: Score,
+}
+impl From<Move> for JsonMove {
+  fn from(move_: Move) -> Self {
+    JsonMove {
+      tile: move_.tile.to_string(),
+      score: move_.score,
+    }
+  }
+}
+
+/// Everything [`analyze_json`] reports
+#[derive(Serialize)]
+struct Analysis {
+  best: Option<JsonMove>,
+  pv: Vec<JsonMove>,
+  candidates: Vec<JsonMove>,
+  verdict: Verdict,
+  stats: Stats,
+}
+
+/// Analyze the board for `player` and return the result as a JSON object.
+///
+/// The object has these fields, tiles written like `"h8"` and scores from the
+/// perspective of `player`:
+/// - `best`: the best move as `{"tile", "score"}`, `null` if the board is full
+/// - `pv`: the best move followed by the expected replies
+/// - `candidates`: up to 5 best moves, best first, like in [`analyze`]
+/// - `verdict`: the [`Verdict`], e.g. `"Win"`
+/// - `stats`: the [`Stats`] of the search
+///
+/// Unlike [`decide`], the opening book and forced blocks are skipped, so
+/// there is always a full search to report on.
+///
+/// # Errors
+/// Returns an error if the engine failed to find a move. See [`GomokuError`]
+/// for possible errors.
+///
+/// [`analyze`]: crate::analyze
+/// [`decide`]: crate::decide
+#[allow(clippy::missing_panics_doc)] // serializing plain structs can't fail
+pub fn analyze_json(board: &Board, player: Player, time_limit: u64) -> Result<String, GomokuError> {
+  let settings = Settings::default();
+
+  let (nodes, stats, verdict) = search(
+    board,
+    player,
+    Limit::Time(Duration::from_millis(time_limit)),
+    &settings,
+    CancelToken::new(),
+  )?;
+
+  let outcome = outcome_from_nodes(board, &nodes, stats, verdict, &settings);
+
+  let analysis = Analysis {
+    best: outcome.best.map(JsonMove::from),
+    pv: outcome.pv.into_iter().map(JsonMove::from).collect(),
+    candidates: nodes
+      .iter()
+      .take(CANDIDATES)
+      .map(|node| node.to_move().into())
+      .collect(),
+    verdict: outcome.verdict,
+    stats: outcome.stats,
+  };
+
+  Ok(serde_json::to_string(&analysis).expect("the analysis is always serializable"))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use serde_json::Value;
+
+  use super::*;
+
+  #[test]
+  fn test_analyze_json() {
+    let board = Board::from_str(
+      "xxxx-----
+---------
+---------
+---------
+----o----
+---o-----
+--o------
+---------
+---------",
+    )
+    .unwrap();
+
+    let json = analyze_json(&board, Player::X, 100).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(value["best"]["tile"], "e1");
+    assert_eq!(value["pv"][0], value["best"]);
+    assert_eq!(value["verdict"], "Win");
+    assert!(value["stats"]["depth_reached"].as_u64().unwrap() >= 1);
+
+    let candidates = value["candidates"].as_array().unwrap();
+    assert!(!candidates.is_empty() && candidates.len() <= CANDIDATES);
+    assert_eq!(candidates[0]["tile"], "e1");
+    assert!(candidates.iter().all(|move_| move_["score"].is_i64()));
+
+    // stripes without a five
+    let players = [Some(Player::X), Some(Player::O)];
+    let tiles = (0..9)
+      .map(|y| (0..9).map(|x| players[(x / 2 + y) % 2]).collect())
+      .collect();
+    let full = Board::new(tiles).unwrap();
+
+    let json = analyze_json(&full, Player::X, 10).unwrap();
+    let value: Value = serde_json::from_str(&json).unwrap();
+
+    assert!(value["best"].is_null());
+    assert_eq!(value["verdict"], "Draw");
+  }
+}
@@ -10,146 +10,559 @@
 #![warn(missing_docs)]
 
 mod board;
+#[cfg(feature = "fen")]
+mod book;
+mod engine;
 mod error;
+mod evaluator;
+mod event;
+mod explain;
+mod game;
+#[cfg(feature = "serde")]
+mod json;
 mod r#move; // r# to allow reserved keyword as name
 mod node;
+mod parallel;
 mod player;
+pub mod protocol;
+mod rules;
+mod session;
+mod skill;
+mod solver;
 mod state;
 mod stats;
 /// Utility functions for creating a frontend
 pub mod utils;
 
 use std::{
-  sync::atomic::{AtomicBool, Ordering},
-  thread,
+  hint::black_box,
   time::{Duration, Instant},
 };
 
-pub use board::{Board, Tile, TilePointer};
-use error::GomokuError;
+pub use board::{
+  Board, DoubleThreat, Error as BoardError, Heuristic, PlaceError, ReplayError, SeqInfo, SeqKind,
+  Threat, Threats, Tile, TilePointer, Undo,
+};
+#[cfg(feature = "fen")]
+pub use book::OpeningBook;
+pub use engine::{Engine, EngineBuilder, SearchConfig, TieBreak};
+#[cfg(feature = "threads")]
+pub use engine::{PonderHandle, SearchHandle};
+pub use error::GomokuError;
+pub use evaluator::{DefaultEvaluator, Evaluator};
+pub use event::SearchEvent;
+pub use explain::Explanation;
+pub use game::{GameState, Status};
 #[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
 use jemallocator::Jemalloc;
+#[cfg(feature = "serde")]
+pub use json::analyze_json;
 pub use player::Player;
 // r# to allow reserved keyword as name
-pub use r#move::Move;
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+pub use r#move::{Move, SearchOutcome, Verdict};
+pub use rules::{Rules, Variant};
+pub use session::Session;
+pub use skill::Skill;
+pub use solver::{find_vcf, solve};
+pub use state::State;
 pub use stats::Stats;
-use utils::{do_run, print_status};
+use utils::{splitmix64, CancelToken};
 
-use crate::{node::Node, state::State};
+use crate::{
+  board::evaluation::{clamp_score, Eval},
+  engine::Settings,
+  node::{Killers, Node},
+  parallel::prelude::*,
+};
 
 #[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
-static END: AtomicBool = AtomicBool::new(false);
-
 type Score = i32;
 
+/// Score of a move completing a five
+///
+/// Moves winning later score one less for every ply until the five, so
+/// quicker wins are preferred. Losing moves score the negation, i.e.
+/// `-(WIN_SCORE - plies)`.
+pub const WIN_SCORE: Score = 1_000_000_000;
+
+/// Largest magnitude of an evaluation of a position or a move
+///
+/// Shape scores are summed in 64 bits and clamped to this, so even boards
+/// full of fives can't overflow and every evaluation stays far below
+/// [`WIN_SCORE`].
+pub const MAX_EVAL: Score = WIN_SCORE / 4;
+
+/// Bound on how long the search runs
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Limit {
+  /// Iterative deepening until the time runs out
+  Time(Duration),
+  /// Search exactly to the given depth, ignoring the clock, 0 is searched as 1
+  Depth(u8),
+}
+
+/// State shared by all nodes of a single search
+pub(crate) struct SearchContext<'a> {
+  /// Token used to stop the search
+  pub token: CancelToken,
+  /// Settings the search runs with
+  pub settings: &'a Settings,
+  /// Time on the settings' clock when the search has to stop, `None` if it
+  /// isn't time limited
+  pub deadline: Option<Duration>,
+  /// Replies that refuted a sibling, tried first
+  pub killers: Killers,
+}
+impl SearchContext<'_> {
+  /// Check if the search should continue running.
+  pub fn do_run(&self) -> bool {
+    !self.token.is_cancelled()
+      && self
+        .deadline
+        .is_none_or(|deadline| self.settings.now() < deadline)
+      && !self.settings.stop_when.as_ref().is_some_and(|stop| stop())
+  }
+}
+
+/// Result of searching one more depth
+enum Iteration {
+  /// Depth completed, search can continue deeper
+  Completed,
+  /// Depth completed and the result is already decided
+  Finished(Verdict),
+  /// Search was interrupted and the depth was rolled back
+  Interrupted,
+}
+
 fn minimax(
-  board: &mut Board,
+  board: &Board,
+  current_player: Player,
+  limit: Limit,
+  settings: &Settings,
+  token: CancelToken,
+) -> Result<SearchOutcome, GomokuError> {
+  minimax_among(board, current_player, None, limit, settings, token)
+}
+
+/// Same as [`minimax`], but only the `candidates` are searched, if given.
+///
+/// The opening book and forced blocks are skipped for candidates, as they
+/// may suggest a move outside of them.
+fn minimax_among(
+  board: &Board,
   current_player: Player,
-  time_limit: Duration,
-) -> Result<(Move, Stats), GomokuError> {
-  let end_time = Instant::now() + time_limit;
+  candidates: Option<&[TilePointer]>,
+  limit: Limit,
+  settings: &Settings,
+  token: CancelToken,
+) -> Result<SearchOutcome, GomokuError> {
+  if candidates.is_none() {
+    #[cfg(feature = "fen")]
+    if let Some(outcome) = book_move(board, current_player, settings) {
+      return Ok(outcome);
+    }
 
-  END.store(false, Ordering::Relaxed);
+    if let Some(outcome) = forced_block(board, current_player, settings) {
+      return Ok(outcome);
+    }
+  }
 
-  thread::spawn(move || {
-    thread::sleep(time_limit * 99 / 100);
-    END.store(true, Ordering::Release);
-  });
+  let (nodes, stats, verdict) =
+    search_among(board, current_player, candidates, limit, settings, token)?;
 
-  let mut nodes = board
-    .pointers_to_empty_tiles()
-    .map(|tile| Node::new(tile, current_player, State::NotEnd))
-    .collect::<Vec<_>>();
+  Ok(outcome_from_nodes(board, &nodes, stats, verdict, settings))
+}
 
+/// Pick the move to play from the searched top-level nodes, sorted best
+/// first, and put together the outcome.
+fn outcome_from_nodes(
+  board: &Board,
+  nodes: &[Node],
+  stats: Stats,
+  verdict: Verdict,
+  settings: &Settings,
+) -> SearchOutcome {
   if nodes.is_empty() {
-    return Err(GomokuError::NoEmptyTiles);
+    return SearchOutcome {
+      best: None,
+      pv: Vec::new(),
+      verdict: Verdict::Draw,
+      stats,
+    };
+  }
+
+  let best_node = settings
+    .skill
+    .choose(nodes, settings.skill_seed ^ board.zobrist());
+
+  // every node is scored for the player making its move, so flip the
+  // opponent's replies to the perspective of the player asking
+  let mut pv = best_node.principal_moves();
+  for reply in pv.iter_mut().skip(1).step_by(2) {
+    reply.score = -reply.score;
+  }
+
+  SearchOutcome {
+    best: Some(best_node.to_move()),
+    pv,
+    verdict,
+    stats,
+  }
+}
+
+/// Find the only move stopping the opponent from completing a five next
+/// turn, so there is no need to search.
+///
+/// Returns `None` if the player can win right away, the opponent has no four
+/// or more than one tile to block, or the rules forbid the block. Fours the
+/// opponent isn't allowed to complete don't need blocking.
+fn forced_block(
+  board: &Board,
+  current_player: Player,
+  settings: &Settings,
+) -> Option<SearchOutcome> {
+  let own = board.threats(current_player);
+  let opponent = board.threats(!current_player);
+
+  // the game already ended, or a five of our own beats blocking
+  if !own.fives.is_empty()
+    || !opponent.fives.is_empty()
+    || !own.fours.is_empty()
+    || !own.open_fours.is_empty()
+  {
+    return None;
+  }
+
+  let mut blocks = opponent
+    .fours
+    .iter()
+    .chain(&opponent.open_fours)
+    .flat_map(|four| four.completions.iter().copied())
+    // a completion the rules forbid to the opponent is no threat
+    .filter(|&tile| !settings.rules.is_forbidden(board, tile, !current_player));
+
+  let tile = blocks.next()?;
+  if blocks.any(|other| other != tile) || settings.rules.is_forbidden(board, tile, current_player) {
+    return None;
+  }
+
+  let mut after = board.clone();
+  after.set_tile(tile, Some(current_player));
+  let (score, _) = settings.evaluate(&after, current_player);
+
+  let move_ = Move { tile, score };
+
+  Some(SearchOutcome {
+    best: Some(move_),
+    pv: vec![move_],
+    verdict: Verdict::Forced,
+    stats: Stats::new(),
+  })
+}
+
+/// Look the position up in the opening book, if there is one.
+///
+/// Book moves the rules don't allow are ignored.
+#[cfg(feature = "fen")]
+fn book_move(board: &Board, current_player: Player, settings: &Settings) -> Option<SearchOutcome> {
+  let tile = settings.book.as_ref()?.get(board, current_player)?;
+
+  if settings.rules.is_forbidden(board, tile, current_player) {
+    return None;
+  }
+
+  let move_ = Move { tile, score: 0 };
+
+  Some(SearchOutcome {
+    best: Some(move_),
+    pv: vec![move_],
+    verdict: Verdict::Book,
+    stats: Stats::new(),
+  })
+}
+
+/// Return [`GomokuError::GameEnd`] if either player already has a five
+/// winning under the `rules`.
+///
+/// `state` is the evaluation for the previous player, which custom evaluators
+/// may report as won even without a five. An overline the rules don't count
+/// is ignored, even though the evaluation sees it as a win.
+fn check_game_end(
+  board: &Board,
+  current_player: Player,
+  state: State,
+  rules: Rules,
+) -> Result<(), GomokuError> {
+  let winner = match board.winner_with(&rules) {
+    Some((player, _)) => Some(player),
+    None if board.winner().is_some() => None,
+    None => state.is_win().then_some(!current_player),
+  };
+
+  match winner {
+    Some(winner) => Err(GomokuError::GameEnd { winner }),
+    None => Ok(()),
+  }
+}
+
+/// Run the search and return the remaining top-level nodes, best first,
+/// along with what it concluded about the position.
+///
+/// Returns no nodes if the board is full.
+///
+/// The first depth always completes, so there is a result even if the search
+/// is cancelled right away.
+fn search(
+  board: &Board,
+  current_player: Player,
+  limit: Limit,
+  settings: &Settings,
+  token: CancelToken,
+) -> Result<(Vec<Node>, Stats, Verdict), GomokuError> {
+  search_among(board, current_player, None, limit, settings, token)
+}
+
+/// Same as [`search`], but the top-level nodes are the `candidates`, if
+/// given, instead of all legal moves.
+///
+/// Candidates must be empty tiles. The ones forbidden by the rules are
+/// dropped.
+fn search_among(
+  board: &Board,
+  current_player: Player,
+  candidates: Option<&[TilePointer]>,
+  limit: Limit,
+  settings: &Settings,
+  token: CancelToken,
+) -> Result<(Vec<Node>, Stats, Verdict), GomokuError> {
+  let start = settings.now();
+
+  let end_time = match limit {
+    Limit::Time(time_limit) => Some(start + time_limit),
+    Limit::Depth(_) => None,
+  };
+
+  let ctx = SearchContext {
+    token,
+    settings,
+    deadline: match limit {
+      Limit::Time(time_limit) => {
+        Some(start + time_limit.saturating_sub(settings.search.safety_margin))
+      },
+      Limit::Depth(_) => None,
+    },
+    killers: Killers::default(),
   };
 
-  let mut total_depth = 0;
   let mut stats = Stats::new();
 
-  let (initial_score, initial_state) = board.evaluate_for(!current_player);
-  if initial_state.is_end() {
-    println!("The game already ended");
-    return Err(GomokuError::GameEnd);
+  let (initial_score, initial_state) = settings.evaluate(board, !current_player);
+  check_game_end(board, current_player, initial_state, settings.rules)?;
+
+  if board.is_full() && settings.rules.can_draw {
+    stats.elapsed = settings.now().saturating_sub(start);
+    return Ok((Vec::new(), stats, Verdict::Draw));
   }
 
-  while do_run() {
-    total_depth += 1;
+  if board
+    .legal_moves_iter(current_player, &settings.rules)
+    .next()
+    .is_none()
+  {
+    return Err(GomokuError::NoLegalMoves {
+      player: current_player,
+    });
+  }
 
-    print_status(
-      &format!(
-        "computing depth {total_depth} for {} nodes",
-        nodes.iter().map(Node::node_count).sum::<usize>()
-      ),
-      &end_time,
-    );
+  let mut nodes = root_nodes(board, current_player, candidates, settings);
 
-    let snapshot = nodes.clone();
+  let mut total_depth = 0;
+  let mut verdict = Verdict::Unclear;
 
-    stats += nodes
-      .par_iter_mut()
-      .map(|node| node.compute_next(&mut board.clone(), initial_score))
-      .sum();
+  while total_depth == 0 || ctx.do_run() {
+    // a zero time limit or depth gets just the first depth, whatever the
+    // clock says, so the move returned is always evaluated
+    let max_depth = match limit {
+      Limit::Depth(max_depth) => Some(max_depth.max(1)),
+      Limit::Time(time_limit) if time_limit.is_zero() => Some(1),
+      Limit::Time(_) => None,
+    };
 
-    if nodes.iter().any(|node| !node.valid) {
-      nodes = snapshot;
-      total_depth -= 1;
+    if max_depth.is_some_and(|max_depth| total_depth >= max_depth) {
       break;
     }
 
-    nodes.sort_unstable_by(|a, b| b.cmp(a));
-
-    if nodes.iter().any(|node| node.state.is_win()) {
-      println!("Winning move found!");
+    if settings
+      .skill
+      .max_depth()
+      .is_some_and(|max_depth| total_depth >= max_depth)
+    {
       break;
     }
 
-    if nodes.iter().all(|node| node.state.is_lose()) {
-      println!("All moves are losing :(");
-      break;
-    }
+    total_depth += 1;
+
+    settings.emit(|| SearchEvent::Depth {
+      depth: total_depth,
+      nodes: nodes.iter().map(Node::node_count).sum(),
+      remaining: end_time.map(|end_time| end_time.saturating_sub(settings.now())),
+    });
+
+    let iteration = search_next_depth(&mut nodes, board, initial_score, &ctx, &mut stats);
 
-    if nodes.iter().all(|node| node.state == State::Draw) {
-      println!("All moves are draws.");
+    if matches!(iteration, Iteration::Interrupted) {
+      total_depth -= 1;
       break;
     }
 
-    nodes.retain(|child| child.state == State::NotEnd);
+    if let Some(best) = nodes.first() {
+      stats.depth_reached = total_depth;
+      stats.elapsed = settings.now().saturating_sub(start);
+
+      settings.emit(|| SearchEvent::Iteration {
+        depth: total_depth,
+        best_move: best.to_move(),
+        stats,
+      });
+    }
 
-    if nodes.len() <= 1 {
-      println!("Only one viable move left");
+    if let Iteration::Finished(final_verdict) = iteration {
+      verdict = final_verdict;
       break;
     }
+  }
+
+  nodes.sort_by(|a, b| b.cmp(a));
 
-    #[allow(
-      clippy::cast_precision_loss,
-      clippy::cast_possible_truncation,
-      clippy::cast_sign_loss
-    )]
-    let moves_count = (2.0 * (nodes.len() as f32).sqrt()) as usize;
-    nodes.truncate(moves_count.max(3));
+  stats.depth_reached = total_depth;
+  stats.elapsed = settings.now().saturating_sub(start);
+
+  if let Some(max_depth) = settings.dump_tree {
+    settings.emit(|| SearchEvent::Tree {
+      dot: Node::dump_tree(&nodes, max_depth),
+    });
   }
 
-  println!("Searched to depth {total_depth:?}!");
+  settings.emit(|| SearchEvent::Finished {
+    depth: total_depth,
+    best_line: nodes
+      .first()
+      .map(Node::principal_variation)
+      .unwrap_or_default(),
+  });
 
-  println!();
+  Ok((nodes, stats, verdict))
+}
 
-  let best_node = nodes.iter().max().expect("we never remove all nodes");
+/// Create the top-level nodes, either from the candidates or all legal moves.
+fn root_nodes(
+  board: &Board,
+  current_player: Player,
+  candidates: Option<&[TilePointer]>,
+  settings: &Settings,
+) -> Vec<Node> {
+  let tiles = if let Some(candidates) = candidates {
+    candidates
+      .iter()
+      .copied()
+      .filter(|&tile| !settings.rules.is_forbidden(board, tile, current_player))
+      .collect::<Vec<_>>()
+  } else {
+    // moves mapped onto each other by a symmetry of the board lead to
+    // equivalent positions, so only one of them has to be searched
+    let symmetries = if settings.symmetry_dedup {
+      board.self_symmetries()
+    } else {
+      Vec::new()
+    };
 
-  println!("Best move sequence: {best_node:#?}");
+    board
+      .legal_moves_iter(current_player, &settings.rules)
+      .filter(|&tile| board.is_first_symmetric(tile, &symmetries))
+      .collect()
+  };
 
-  Ok((best_node.to_move(), stats))
+  tiles
+    .into_iter()
+    .map(|tile| Node::new(tile, current_player, State::NotEnd))
+    .collect()
+}
+
+/// Compute one more depth of the top-level nodes.
+///
+/// On interruption `nodes` are restored to the last completed depth, keeping
+/// only their principal variations, as the search stops anyway.
+fn search_next_depth(
+  nodes: &mut Vec<Node>,
+  board: &Board,
+  initial_score: Score,
+  ctx: &SearchContext,
+  stats: &mut Stats,
+) -> Iteration {
+  let snapshot = nodes.iter().map(Node::summary).collect();
+
+  *stats += nodes
+    .par_iter_mut()
+    .map(|node| node.compute_next(&mut board.clone(), initial_score, ctx))
+    .sum();
+
+  if nodes.iter().any(|node| !node.valid) {
+    *nodes = snapshot;
+    return Iteration::Interrupted;
+  }
+
+  nodes.sort_unstable_by(|a, b| b.cmp(a));
+
+  if nodes.iter().any(|node| node.state.is_win()) {
+    ctx.settings.emit(|| SearchEvent::WinFound);
+    return Iteration::Finished(Verdict::Win);
+  }
+
+  if nodes.iter().all(|node| node.state.is_lose()) {
+    ctx.settings.emit(|| SearchEvent::AllLosing);
+    return Iteration::Finished(Verdict::Loss);
+  }
+
+  if nodes.iter().all(|node| node.state == State::Draw) {
+    ctx.settings.emit(|| SearchEvent::AllDraws);
+    return Iteration::Finished(Verdict::Draw);
+  }
+
+  nodes.retain(|child| child.state == State::NotEnd);
+
+  if nodes.len() <= 1 {
+    ctx.settings.emit(|| SearchEvent::OneMoveLeft);
+    return Iteration::Finished(Verdict::Forced);
+  }
+
+  let beam = ctx
+    .settings
+    .skill
+    .beam()
+    .min(ctx.settings.search.top_level_beam);
+  #[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+  )]
+  let moves_count = (beam * (nodes.len() as f32).sqrt()) as usize;
+  nodes.truncate(moves_count.max(3));
+
+  Iteration::Completed
 }
 
 /// Sets the thread count for the rayon threadpool
 ///
+/// Only affects the free functions, as every [`Engine`] searches in its own
+/// pool. Can be called just once per process.
+///
 /// # Errors
 /// Returns an error if the thread count is already set.
+#[cfg(feature = "threads")]
+#[deprecated(note = "use `EngineBuilder::threads` or `EngineBuilder::thread_pool` instead")]
 pub fn set_thread_count(threads: usize) -> Result<(), Box<dyn std::error::Error>> {
   rayon::ThreadPoolBuilder::new()
     .num_threads(threads)
@@ -157,7 +570,14 @@ pub fn set_thread_count(threads: usize) -> Result<(), Box<dyn std::error::Error>
     .map_err(|_| "Thread count already set".into())
 }
 
-/// Returns the best move and stats for the given board.
+/// Returns the best move and stats for the given board and plays the move.
+///
+/// If the board is full, there is no move to play and the verdict is
+/// [`Verdict::Draw`].
+///
+/// The `time_limit` is in milliseconds. The first depth always completes, so
+/// there is a move even when time runs out; with `0` the search stops right
+/// after it and returns the best move by a shallow evaluation.
 ///
 /// # Errors
 /// Returns an error if the engine failed to find a move. See [`GomokuError`]
@@ -166,12 +586,603 @@ pub fn decide(
   board: &mut Board,
   player: Player,
   time_limit: u64,
-) -> Result<(Move, Stats), GomokuError> {
+) -> Result<SearchOutcome, GomokuError> {
   let time_limit = Duration::from_millis(time_limit);
 
-  let (move_, stats) = minimax(board, player, time_limit)?;
+  let outcome = minimax(
+    board,
+    player,
+    Limit::Time(time_limit),
+    &Settings::default(),
+    CancelToken::new(),
+  )?;
+
+  if let Some(move_) = outcome.best {
+    board.set_tile(move_.tile, Some(player));
+  }
+
+  Ok(outcome)
+}
+
+/// Returns up to `n` best moves for the given board, best first.
+///
+/// Scores are from the perspective of `player` and winning moves are always
+/// sorted above the rest. Fewer than `n` moves may be returned, because the
+/// search discards clearly inferior moves as it deepens, and none if the
+/// board is full.
+///
+/// # Errors
+/// Returns an error if the engine failed to find a move. See [`GomokuError`]
+/// for possible errors.
+pub fn analyze(
+  board: &Board,
+  player: Player,
+  time_limit: u64,
+  n: usize,
+) -> Result<Vec<Move>, GomokuError> {
+  let time_limit = Duration::from_millis(time_limit);
+
+  let (nodes, ..) = search(
+    board,
+    player,
+    Limit::Time(time_limit),
+    &Settings::default(),
+    CancelToken::new(),
+  )?;
+
+  Ok(nodes.iter().take(n).map(Node::to_move).collect())
+}
+
+/// Returns the best move and stats for the given board, searching exactly to
+/// the given depth, and plays the move.
+///
+/// If the board is full, there is no move to play and the verdict is
+/// [`Verdict::Draw`]. A depth of 0 is searched as 1, since there has to be
+/// an evaluated move to play.
+///
+/// Ignores the clock, so the run time depends only on the position and depth.
+/// Useful for reproducible tests and puzzle solving.
+///
+/// # Errors
+/// Returns an error if the engine failed to find a move. See [`GomokuError`]
+/// for possible errors.
+pub fn decide_to_depth(
+  board: &mut Board,
+  player: Player,
+  depth: u8,
+) -> Result<SearchOutcome, GomokuError> {
+  let outcome = minimax(
+    board,
+    player,
+    Limit::Depth(depth),
+    &Settings::default(),
+    CancelToken::new(),
+  )?;
+
+  if let Some(move_) = outcome.best {
+    board.set_tile(move_.tile, Some(player));
+  }
+
+  Ok(outcome)
+}
+
+/// Measure how many full board evaluations per second the current thread
+/// pool can do.
+///
+/// Evaluates a fixed set of pseudo-random positions on a square board of the
+/// given size from scratch, so the numbers are comparable across runs.
+///
+/// # Panics
+/// Panics if the size is below [`Board::MIN_SIZE`].
+pub fn benchmark_evals(board_size: u8, duration: Duration) -> u64 {
+  const POSITIONS: u64 = 64;
+
+  let tiles = u64::from(board_size).pow(2);
+
+  // fill about a quarter of the board
+  let boards = (0..POSITIONS)
+    .map(|seed| random_position(board_size, seed, tiles / 4))
+    .collect::<Vec<_>>();
+
+  let end = Instant::now() + duration;
+
+  let evals = (0..current_num_threads())
+    .into_par_iter()
+    .map(|thread| {
+      let mut count = 0;
+
+      while Instant::now() < end {
+        let board = &boards[(thread + count) % boards.len()];
+        black_box(board.evaluate_uncached(&Heuristic::DEFAULT));
+        count += 1;
+      }
+
+      count as u64
+    })
+    .sum::<u64>();
+
+  (evals as f64 / duration.as_secs_f64()) as u64
+}
+
+/// Get the canonical set of positions for benchmarks.
+///
+/// Boards of [`Board::DEFAULT_SIZE`] filled from fixed seeds, ranging from
+/// the opening to a crowded middlegame. The set is the same on every call, so
+/// results are comparable across runs and commits.
+pub fn benchmark_positions() -> Vec<Board> {
+  const FILLS: [u64; 4] = [4, 16, 40, 70];
+  const PER_FILL: u64 = 4;
+
+  FILLS
+    .iter()
+    .enumerate()
+    .flat_map(|(stage, &fill)| {
+      (0..PER_FILL)
+        .map(move |i| random_position(Board::DEFAULT_SIZE, stage as u64 * PER_FILL + i, fill))
+    })
+    .collect()
+}
+
+/// Evaluate the board from scratch for `player`, for benchmarking the
+/// evaluator.
+///
+/// Unlike [`Board::evaluate_for`], this ignores the cached evaluation, so
+/// every call does the full work. The result is the same.
+pub fn bench_evaluate_board(board: &Board, player: Player) -> (Score, State) {
+  let Eval { score, win } = black_box(board.evaluate_uncached(&Heuristic::DEFAULT));
+
+  let state = if win[player] {
+    State::Win
+  } else {
+    State::NotEnd
+  };
+
+  (clamp_score(score[player] - score[!player]), state)
+}
+
+/// Search the board to exactly the given depth, for benchmarking the search.
+///
+/// Same as [`decide_to_depth`], but leaves the board untouched, so the same
+/// position can be searched repeatedly.
+///
+/// # Errors
+/// Returns an error if the engine failed to find a move. See [`GomokuError`]
+/// for possible errors.
+pub fn bench_decide_fixed_depth(
+  board: &Board,
+  player: Player,
+  depth: u8,
+) -> Result<SearchOutcome, GomokuError> {
+  minimax(
+    board,
+    player,
+    Limit::Depth(depth),
+    &Settings::default(),
+    CancelToken::new(),
+  )
+}
+
+/// Make a square board with up to `stones` pseudo-random tiles placed
+/// alternately by X and O, derived only from `seed`.
+///
+/// # Panics
+/// Panics if the size is below [`Board::MIN_SIZE`].
+fn random_position(board_size: u8, seed: u64, stones: u64) -> Board {
+  let tiles = u64::from(board_size).pow(2);
+  let mut board = Board::new_empty(board_size).expect("board size is too small");
+
+  for i in 0..stones {
+    let index = splitmix64(seed * tiles + i) % tiles;
+    let tile = board.get_ptr_from_index(index as usize);
+
+    if board.get_tile(tile).is_none() {
+      let player = if i % 2 == 0 { Player::X } else { Player::O };
+      board.set_tile(tile, Some(player));
+    }
+  }
+
+  board
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+
+  #[test]
+  fn test_decide_to_depth_finds_immediate_win() {
+    let mut board = Board::from_str(
+      "xxxx-----
+---------
+---------
+---------
+----o----
+---o-----
+--o------
+---------
+---------",
+    )
+    .unwrap();
+
+    let outcome = decide_to_depth(&mut board, Player::X, 1).unwrap();
+    let move_ = outcome.best_move().unwrap();
+
+    assert_eq!(outcome.stats.depth_reached, 1);
+    assert_eq!(outcome.verdict, Verdict::Win);
+    assert_eq!(outcome.pv, vec![move_]);
+
+    assert_eq!(move_.tile, TilePointer { x: 4, y: 0 });
+    assert_eq!(board.winner().map(|(player, _)| player), Some(Player::X));
+  }
+
+  #[test]
+  fn test_decide_to_depth_zero_searches_one() {
+    let board = Board::from_str(
+      "xxxx-----
+---------
+---------
+---------
+----o----
+---o-----
+--o------
+---------
+---------",
+    )
+    .unwrap();
+
+    let outcome = decide_to_depth(&mut board.clone(), Player::X, 0).unwrap();
+
+    assert_eq!(outcome.stats.depth_reached, 1);
+    assert!(outcome.stats.nodes_evaluated > 0);
+    assert_eq!(outcome.verdict, Verdict::Win);
+    assert_eq!(
+      outcome.best_move().unwrap().tile,
+      TilePointer { x: 4, y: 0 }
+    );
+  }
+
+  #[test]
+  fn test_quiescence_sees_delayed_double_four() {
+    // no move makes a double four right away, but e.g. X fours at (4, 4),
+    // O has to block at (5, 4) and X then makes a double four at (4, 7)
+    let board = Board::from_str(
+      "---------
+---------
+---------
+----o----
+oxxx-----
+----x----
+----x----
+-----xxxo
+------oo-",
+    )
+    .unwrap();
+
+    assert_eq!(find_vcf(&board, Player::X, 1), None);
+
+    let (nodes, ..) = search(
+      &board,
+      Player::X,
+      Limit::Depth(1),
+      &Settings::default(),
+      CancelToken::new(),
+    )
+    .unwrap();
+
+    assert_eq!(nodes[0].state, State::Win);
+  }
+
+  #[test]
+  fn test_prefers_quicker_win() {
+    // a five at (4, 0) or an open four at (2, 6) or (6, 6)
+    let board = Board::from_str(
+      "xxxx-----
+---------
+---------
+---------
+-----oo--
+---------
+---xxx---
+---------
+-----o---",
+    )
+    .unwrap();
 
-  board.set_tile(move_.tile, Some(player));
+    let (nodes, ..) = search(
+      &board,
+      Player::X,
+      Limit::Depth(1),
+      &Settings::default(),
+      CancelToken::new(),
+    )
+    .unwrap();
 
-  Ok((move_, stats))
+    let wins = nodes
+      .iter()
+      .filter(|node| node.state == State::Win)
+      .map(Node::to_move)
+      .collect::<Vec<_>>();
+
+    assert!(wins.len() >= 3);
+    assert_eq!(wins[0].tile, TilePointer { x: 4, y: 0 });
+    assert!(wins[1..].iter().all(|move_| move_.score < wins[0].score));
+  }
+
+  #[test]
+  fn test_heatmap_matches_search() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---xo----
+----x----
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let heatmap = board.heatmap(Player::O);
+    assert_eq!(heatmap.len(), 81 - 3);
+
+    let (nodes, ..) = search(
+      &board,
+      Player::O,
+      Limit::Depth(1),
+      &Settings::default(),
+      CancelToken::new(),
+    )
+    .unwrap();
+
+    for node in nodes {
+      let Move { tile, score } = node.to_move();
+      assert!(heatmap.contains(&(tile, score)), "{tile} scores {score}");
+    }
+  }
+
+  #[test]
+  fn test_full_board_is_draw() {
+    let mut board = Board::from_str(
+      "xxooxxoox
+ooxxooxxo
+xxooxxoox
+ooxxooxxo
+xxooxxoox
+ooxxooxxo
+xxooxxoox
+ooxxooxxo
+xxooxxoox",
+    )
+    .unwrap();
+
+    assert_eq!(board.winner(), None);
+
+    let outcome = decide_to_depth(&mut board, Player::X, 1).unwrap();
+
+    assert!(outcome.is_draw());
+    assert_eq!(outcome.verdict, Verdict::Draw);
+    assert!(analyze(&board, Player::X, 100, 5).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_scores_from_asking_player() {
+    // X is far ahead, whoever is on turn
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---x-x---
+----x----
+---------
+---------
+-------o-
+---------",
+    )
+    .unwrap();
+
+    for (player, sign) in [(Player::X, 1), (Player::O, -1)] {
+      let outcome = decide_to_depth(&mut board.clone(), player, 2).unwrap();
+
+      assert_eq!(outcome.best, outcome.pv.first().copied());
+      assert!(outcome.pv.len() > 1);
+
+      for move_ in &outcome.pv {
+        assert_eq!(move_.score.signum(), sign, "{player}: {:?}", outcome.pv);
+      }
+    }
+  }
+
+  #[test]
+  fn test_forced_block() {
+    let mut board = Board::from_str(
+      "---------
+---------
+-oxxxx---
+---------
+---o-----
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    let outcome = decide_to_depth(&mut board.clone(), Player::O, 4).unwrap();
+
+    assert_eq!(outcome.verdict, Verdict::Forced);
+    assert_eq!(outcome.stats.nodes_evaluated, 0);
+    assert_eq!(
+      outcome.best_move().map(|move_| move_.tile),
+      Some(TilePointer { x: 6, y: 2 })
+    );
+
+    // a five of our own comes first
+    board.set_tile(TilePointer { x: 3, y: 5 }, Some(Player::O));
+    board.set_tile(TilePointer { x: 3, y: 6 }, Some(Player::O));
+    board.set_tile(TilePointer { x: 3, y: 7 }, Some(Player::O));
+
+    let outcome = decide_to_depth(&mut board, Player::O, 1).unwrap();
+
+    assert_eq!(outcome.verdict, Verdict::Win);
+    assert_eq!(board.winner().map(|(player, _)| player), Some(Player::O));
+  }
+
+  #[test]
+  fn test_won_board_reports_winner() {
+    let mut board = Board::from_str(
+      "ooooo----
+xxxx-----
+---------
+---------
+---------
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    for player in Player::all() {
+      assert!(matches!(
+        decide_to_depth(&mut board, player, 1),
+        Err(GomokuError::GameEnd { winner: Player::O })
+      ));
+    }
+  }
+
+  #[test]
+  fn test_benchmark_evals() {
+    assert!(benchmark_evals(9, Duration::from_millis(20)) > 0);
+  }
+
+  #[test]
+  fn test_symmetry_dedup() {
+    let settings = Settings {
+      symmetry_dedup: true,
+      ..Settings::default()
+    };
+
+    let count_moves = |board: &Board| root_nodes(board, Player::X, None, &settings).len();
+
+    let mut board = Board::new_empty(9).unwrap();
+
+    // one eighth of the board including the axes, 0 <= x <= y <= 4
+    assert_eq!(count_moves(&board), 15);
+
+    board.set_tile(TilePointer { x: 4, y: 4 }, Some(Player::O));
+    assert_eq!(count_moves(&board), 14);
+
+    // only symmetric along the main diagonal
+    board.set_tile(TilePointer { x: 1, y: 1 }, Some(Player::O));
+    assert_eq!(count_moves(&board), (81 - 9) / 2 + 9 - 2);
+
+    board.set_tile(TilePointer { x: 1, y: 2 }, Some(Player::O));
+    assert_eq!(count_moves(&board), 81 - 3);
+  }
+
+  #[test]
+  fn test_analyze_sorted() {
+    let board = Board::from_str(
+      "xxxx-----
+---------
+---------
+---------
+----o----
+---o-----
+--o------
+---------
+---------",
+    )
+    .unwrap();
+
+    let moves = analyze(&board, Player::X, 100, 5).unwrap();
+
+    assert!(!moves.is_empty() && moves.len() <= 5);
+    assert_eq!(moves[0].tile, TilePointer { x: 4, y: 0 });
+  }
+
+  #[test]
+  fn test_decide_zero_time_limit() {
+    let mut board = Board::new_empty(15).unwrap();
+    board.set_tile(TilePointer { x: 7, y: 7 }, Some(Player::X));
+
+    let start = Instant::now();
+    let outcome = decide(&mut board, Player::O, 0).unwrap();
+
+    let tile = outcome.best.unwrap().tile;
+    assert_ne!(tile, TilePointer { x: 7, y: 7 });
+    assert_eq!(*board.get_tile(tile), Some(Player::O));
+    assert_eq!(outcome.stats.depth_reached, 1);
+    assert!(start.elapsed() < Duration::from_millis(500));
+  }
+
+  #[test]
+  fn test_large_boards() {
+    let mut board = Board::new_empty(32).unwrap();
+
+    for (x, y) in [(30, 30), (31, 30), (29, 31), (0, 0), (31, 0), (16, 16)] {
+      board.set_tile(TilePointer { x, y }, Some(Player::X));
+    }
+    for (x, y) in [(30, 31), (28, 28), (0, 31), (15, 16)] {
+      board.set_tile(TilePointer { x, y }, Some(Player::O));
+    }
+
+    assert_eq!(Board::new(board.to_grid()).unwrap(), board);
+    assert!(board
+      .to_string()
+      .lines()
+      .nth(1)
+      .unwrap()
+      .ends_with("xyzabcdef"));
+
+    let far = TilePointer { x: 31, y: 31 };
+    assert_eq!(TilePointer::from_str(&far.to_string()).unwrap(), far);
+
+    let outcome = decide_to_depth(&mut board.clone(), Player::O, 2).unwrap();
+    let best = outcome.best_move().unwrap().tile;
+    assert!(board.get(best) == Some(&None));
+
+    // the largest size still builds and evaluates, but is too slow to search
+    let mut largest = Board::new_empty(u8::MAX).unwrap();
+    let corner = TilePointer { x: 254, y: 254 };
+    largest.set_tile(corner, Some(Player::X));
+    assert_eq!(largest.sequences().len(), 6 * 255 - 2);
+    assert_eq!(
+      largest.evaluate_for(Player::X),
+      bench_evaluate_board(&largest, Player::X)
+    );
+    let next = TilePointer { x: 252, y: 254 };
+    let mut after = largest.clone();
+    after.set_tile(next, Some(Player::X));
+    assert_eq!(
+      largest.score_move(next, Player::X),
+      after.evaluate_for(Player::X)
+    );
+  }
+
+  #[test]
+  fn test_bench_entry_points() {
+    let positions = benchmark_positions();
+
+    assert_eq!(positions.len(), 16);
+    assert_eq!(positions, benchmark_positions());
+
+    for board in &positions {
+      for player in [Player::X, Player::O] {
+        assert_eq!(
+          bench_evaluate_board(board, player),
+          board.evaluate_for(player)
+        );
+      }
+    }
+
+    let board = &positions[0];
+    let first = bench_decide_fixed_depth(board, Player::X, 2).unwrap();
+    let second = bench_decide_fixed_depth(board, Player::X, 2).unwrap();
+
+    assert_eq!(first.best, second.best);
+    assert_eq!(*board, positions[0]);
+  }
 }
@@ -1,10 +1,12 @@
 use std::fmt;
 
-use super::{Score, TilePointer};
+use super::{Score, Stats, TilePointer};
 
 /// A move in the game
 ///
 /// Consists of a target tile and a score, independent of the player
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
   /// Target tile
   pub tile: TilePointer,
@@ -16,3 +18,71 @@ impl fmt::Debug for Move {
     write!(f, "({:?}, {})", self.tile, self.score)
   }
 }
+impl fmt::Display for Move {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} (score {:+})", self.tile, self.score)
+  }
+}
+
+/// Conclusion the search reached about the position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Verdict {
+  /// The best move leads to a win
+  Win,
+  /// All moves lead to a loss
+  Loss,
+  /// All moves lead to a draw, or the board is full
+  Draw,
+  /// All moves except the best one lead to a loss or a draw
+  Forced,
+  /// The search ran out of time or depth before deciding the game
+  Unclear,
+  /// The move comes from the opening book, there was no search
+  Book,
+}
+
+/// Result of a search
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchOutcome {
+  /// The best move found, `None` if the board is full
+  ///
+  /// Scored from the perspective of the player the search was for, positive
+  /// means good for them.
+  pub best: Option<Move>,
+  /// The best move followed by the expected replies
+  ///
+  /// All moves are scored from the perspective of the player the search was
+  /// for, including the opponent's replies.
+  pub pv: Vec<Move>,
+  /// What the search concluded about the position
+  pub verdict: Verdict,
+  /// Stats of the search
+  pub stats: Stats,
+}
+impl SearchOutcome {
+  /// Get the best move, or `None` in case of a draw.
+  pub fn best_move(&self) -> Option<Move> {
+    self.best
+  }
+
+  /// Check if there is no move to play because the board is full.
+  pub fn is_draw(&self) -> bool {
+    self.best.is_none()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    let tile = TilePointer { x: 7, y: 7 };
+
+    assert_eq!(Move { tile, score: 1234 }.to_string(), "h8 (score +1234)");
+    assert_eq!(Move { tile, score: -5 }.to_string(), "h8 (score -5)");
+    assert_eq!(Move { tile, score: 0 }.to_string(), "h8 (score +0)");
+  }
+}
@@ -1,17 +1,66 @@
-use std::{cmp::Ordering, fmt};
-
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use std::{
+  cmp::Ordering,
+  fmt::{self, Write},
+  iter,
+  sync::atomic::{AtomicU32, Ordering as AtomicOrdering},
+};
 
 use super::{
-  board::{evaluation::Eval, Board, TilePointer},
+  board::{
+    evaluation::{clamp_score, Eval},
+    Board, TilePointer,
+  },
+  evaluator::Evaluator,
+  parallel::prelude::*,
   player::Player,
   r#move::Move,
+  solver::quiescence,
   state::State,
   stats::Stats,
-  utils::{do_run, signed_sqrt},
-  Score,
+  utils::signed_sqrt,
+  Score, SearchConfig, SearchContext, WIN_SCORE,
 };
 
+/// How many more moves to extend the search by when following fours
+const QUIESCENCE_DEPTH: u8 = 4;
+
+/// Killer moves, replies that completed a five against a sibling node
+///
+/// Such a reply often refutes the other siblings as well, so trying it first
+/// lets a node stop initializing its children right away. Nodes are expanded
+/// only at the frontier, which is at the same ply for all nodes of one
+/// iteration, so a slot for each player replying is the whole per-depth
+/// table. Shared by all search threads.
+#[derive(Default)]
+pub(crate) struct Killers([AtomicU32; 2]);
+impl Killers {
+  fn slot(&self, player: Player) -> &AtomicU32 {
+    match player {
+      Player::X => &self.0[0],
+      Player::O => &self.0[1],
+    }
+  }
+
+  /// Get the last reply of `player` that completed a five.
+  fn get(&self, player: Player) -> Option<TilePointer> {
+    // zero is an empty slot, so tiles are stored shifted by one
+    let packed = self
+      .slot(player)
+      .load(AtomicOrdering::Relaxed)
+      .checked_sub(1)?;
+
+    Some(TilePointer {
+      x: (packed >> 8) as u8,
+      y: packed as u8,
+    })
+  }
+
+  fn store(&self, player: Player, tile: TilePointer) {
+    let packed = (u32::from(tile.x) << 8 | u32::from(tile.y)) + 1;
+    self.slot(player).store(packed, AtomicOrdering::Relaxed);
+  }
+}
+
 #[derive(Clone)]
 pub struct Node {
   tile: TilePointer,
@@ -21,55 +70,129 @@ pub struct Node {
   child_nodes: Vec<Node>,
 
   score: Score,
+  /// Breaks ties between nodes with equal score, lower is better
+  tie_key: u64,
   first_score: Score,
   first_score_sqrt: Score,
   depth: u8,
 }
 impl Node {
-  pub fn compute_next(&mut self, board: &mut Board, parent_score: Score) -> Stats {
+  pub fn compute_next(
+    &mut self,
+    board: &mut Board,
+    parent_score: Score,
+    ctx: &SearchContext,
+  ) -> Stats {
     debug_assert!(!self.state.is_end());
 
     let mut stats = Stats::new();
 
-    if !do_run() {
-      self.valid = false;
+    // the first depth is cheap, so it's never interrupted to always have
+    // some result
+    if self.depth == 0 {
+      self.depth = 1;
+      self.initialize(board, parent_score, ctx, &mut stats);
+      self.set_mate_score(1);
+
+      // extend forcing moves at the horizon, so a four doesn't hide a win
+      if self.state == State::NotEnd {
+        if let Some(plies) = quiescence(
+          board,
+          self.tile,
+          self.player,
+          QUIESCENCE_DEPTH,
+          ctx.settings.rules,
+        ) {
+          self.state = State::Win;
+          self.set_mate_score(Score::from(plies));
+        }
+      }
+
       return stats;
     }
 
-    self.depth += 1;
-
-    if self.depth == 1 {
-      self.initialize(board, parent_score, &mut stats);
+    if !ctx.do_run() {
+      self.valid = false;
       return stats;
     }
 
+    self.depth += 1;
+
     board.set_tile(self.tile, Some(self.player));
 
     if self.depth == 2 {
       self.child_nodes = board
-        .pointers_to_empty_tiles()
+        .legal_moves_iter(!self.player, &ctx.settings.rules)
         .map(|tile| Node::new(tile, !self.player, State::NotEnd))
         .collect();
 
       if self.child_nodes.is_empty() {
-        self.state = State::Draw;
-        self.score = 0;
+        if board.is_full() && ctx.settings.rules.can_draw {
+          self.state = State::Draw;
+          self.score = 0;
+        } else {
+          // the opponent can't move, because the rules forbid every empty
+          // tile or don't allow a draw, so they lose
+          self.state = State::Win;
+          self.set_mate_score(2);
+        }
+
         return stats;
       }
+
+      stats += self.initialize_children(board, ctx);
+    } else {
+      stats += self
+        .child_nodes
+        .par_iter_mut()
+        .map(|node| node.compute_next(&mut board.clone(), self.first_score, ctx))
+        .sum();
     }
 
-    stats += self
-      .child_nodes
-      .par_iter_mut()
-      .map(|node| node.compute_next(&mut board.clone(), self.first_score))
-      .sum();
+    self.evaluate_children(&ctx.settings.search);
+
+    stats
+  }
+
+  /// Initialize the freshly created children one by one, the killer move
+  /// first, stopping at the first reply completing a five.
+  ///
+  /// Such a reply scores the best possible, so the node is lost no matter the
+  /// other replies and the result is the same as with all of them.
+  fn initialize_children(&mut self, board: &mut Board, ctx: &SearchContext) -> Stats {
+    let mut stats = Stats::new();
 
-    self.evaluate_children();
+    if ctx.settings.search.killer_moves {
+      let killer = ctx.killers.get(!self.player);
+
+      if let Some(index) = killer.and_then(|killer| {
+        self
+          .child_nodes
+          .iter()
+          .position(|child| child.tile == killer)
+      }) {
+        self.child_nodes.swap(0, index);
+      }
+    }
+
+    for child in &mut self.child_nodes {
+      stats += child.compute_next(board, self.first_score, ctx);
+
+      if child.wins_immediately() {
+        ctx.killers.store(child.player, child.tile);
+        break;
+      }
+    }
 
     stats
   }
 
-  fn evaluate_children(&mut self) {
+  /// Check if the node's move completes a five, or otherwise wins right away.
+  fn wins_immediately(&self) -> bool {
+    self.state == State::Win && self.score == WIN_SCORE - 1
+  }
+
+  fn evaluate_children(&mut self, config: &SearchConfig) {
     debug_assert!(
       !self.child_nodes.is_empty(),
       "Children empty while state is {}",
@@ -83,14 +206,7 @@ impl Node {
 
     self.child_nodes.sort_unstable_by(|a, b| b.cmp(a));
 
-    let limit = match self.depth {
-      0 | 1 => unreachable!("depth 0 or 1 means the chilren are yet to be initialized"),
-      2 => (self.child_nodes.len() / 2).max(24),
-      3 => 16,
-      4..=7 => 8,
-      8 => 4,
-      9.. => 2,
-    };
+    let limit = config.replies_kept(self.depth, self.child_nodes.len());
 
     self.child_nodes.truncate(limit);
 
@@ -104,6 +220,10 @@ impl Node {
     self.score = self.first_score_sqrt - best.score / 2;
     self.state = best.state.inversed();
 
+    if matches!(best.state, State::Win | State::Lose) {
+      self.set_mate_score(WIN_SCORE - best.score.abs() + 1);
+    }
+
     if self.state != State::NotEnd {
       self.child_nodes = Vec::new();
       return;
@@ -114,34 +234,33 @@ impl Node {
       .retain(|child| child.state == State::NotEnd);
   }
 
-  fn initialize(&mut self, board: &mut Board, parent_score: Score, stats: &mut Stats) {
+  fn initialize(
+    &mut self,
+    board: &mut Board,
+    parent_score: Score,
+    ctx: &SearchContext,
+    stats: &mut Stats,
+  ) {
     stats.evaluate_node();
 
-    let opponent = !self.player;
-    let mut score = parent_score;
-    let tile = self.tile;
+    self.tie_key = ctx.settings.tie_break.key(board, self.tile);
 
-    score += 20 * board.squared_distance_from_center(tile);
-
-    let Eval {
-      score: prev_score, ..
-    } = board.evaluate_sequences_relevant_to(tile);
-
-    score += prev_score[self.player];
-    score -= prev_score[opponent];
-
-    board.set_tile(tile, Some(self.player));
-
-    let Eval {
-      score: new_score,
-      win: new_win,
-    } = board.evaluate_sequences_relevant_to(tile);
+    if let Some(evaluator) = &ctx.settings.evaluator {
+      self.initialize_with(
+        evaluator.as_ref(),
+        board,
+        parent_score,
+        ctx.settings.heuristic.center_bias,
+      );
+      return;
+    }
 
-    score *= -1;
-    score += new_score[self.player];
-    score -= new_score[opponent];
+    let opponent = !self.player;
+    let tile = self.tile;
 
-    board.set_tile(tile, None);
+    let (gain, Eval { win: new_win, .. }) =
+      board.move_gain(tile, self.player, &ctx.settings.heuristic);
+    let score = clamp_score(i64::from(gain) - i64::from(parent_score));
 
     self.score = score;
     self.first_score = score;
@@ -162,6 +281,140 @@ impl Node {
     };
   }
 
+  /// Replace the score of a won or lost node by one encoding the distance to
+  /// the five, so quicker wins and slower losses are preferred.
+  ///
+  /// `plies` is the number of moves of both players until the five,
+  /// including this node's move.
+  fn set_mate_score(&mut self, plies: Score) {
+    match self.state {
+      State::Win => self.score = WIN_SCORE - plies,
+      State::Lose => self.score = -(WIN_SCORE - plies),
+      State::NotEnd | State::Draw => {},
+    }
+  }
+
+  /// Same as [`initialize`](Self::initialize), but evaluates the whole board
+  /// using a custom evaluator.
+  fn initialize_with(
+    &mut self,
+    evaluator: &dyn Evaluator,
+    board: &mut Board,
+    parent_score: Score,
+    center_bias: Score,
+  ) {
+    let mut score = i64::from(parent_score);
+    let tile = self.tile;
+
+    score += i64::from(center_bias) * i64::from(board.squared_distance_from_center(tile));
+
+    let (prev_score, _) = evaluator.evaluate(board, self.player);
+    score += i64::from(prev_score);
+
+    let undo = board.apply(tile, self.player);
+
+    let (new_score, state) = evaluator.evaluate(board, self.player);
+    score *= -1;
+    score += i64::from(new_score);
+
+    board.undo(undo);
+
+    let score = clamp_score(score);
+
+    self.score = score;
+    self.first_score = score;
+    self.first_score_sqrt = signed_sqrt(score);
+    self.state = state;
+  }
+
+  /// Copy the node with only its principal variation instead of the whole
+  /// subtree.
+  ///
+  /// Enough to report the result of the search, much cheaper than a full
+  /// clone.
+  pub fn summary(&self) -> Node {
+    Node {
+      tile: self.tile,
+      player: self.player,
+      state: self.state,
+      valid: self.valid,
+      child_nodes: self
+        .child_nodes
+        .first()
+        .map(Node::summary)
+        .into_iter()
+        .collect(),
+      score: self.score,
+      tie_key: self.tie_key,
+      first_score: self.first_score,
+      first_score_sqrt: self.first_score_sqrt,
+      depth: self.depth,
+    }
+  }
+
+  /// Get the tile of this node followed by the best replies found so far.
+  pub fn principal_variation(&self) -> Vec<TilePointer> {
+    self.principal_nodes().map(|node| node.tile).collect()
+  }
+
+  /// Get the move of this node followed by the best replies found so far.
+  ///
+  /// Scores are from the perspective of the player making each move.
+  pub fn principal_moves(&self) -> Vec<Move> {
+    self.principal_nodes().map(Node::to_move).collect()
+  }
+
+  /// Walk the best children down the tree, stopping at the first node that
+  /// ends the game.
+  fn principal_nodes(&self) -> impl Iterator<Item = &Node> {
+    iter::successors(Some(self), |node| {
+      if node.state.is_end() {
+        None
+      } else {
+        node.child_nodes.first()
+      }
+    })
+  }
+
+  /// Render the trees of the given nodes as a Graphviz DOT graph, down to
+  /// `max_depth` levels below them.
+  pub fn dump_tree(nodes: &[Node], max_depth: u8) -> String {
+    let mut dot = String::from("digraph tree {\n  root [label=\"root\"];\n");
+    let mut next_id = 0;
+
+    for node in nodes {
+      node.write_dot(&mut dot, "root", &mut next_id, max_depth);
+    }
+
+    dot.push_str("}\n");
+    dot
+  }
+
+  fn write_dot(&self, dot: &mut String, parent: &str, next_id: &mut usize, max_depth: u8) {
+    let id = format!("n{next_id}");
+    *next_id += 1;
+
+    let color = match self.state {
+      State::Win => ", color=green",
+      State::Lose => ", color=red",
+      State::Draw => ", color=gray",
+      State::NotEnd => "",
+    };
+
+    writeln!(
+      dot,
+      "  {id} [label=\"{} {}\\n{}\\ndepth {}\"{color}];\n  {parent} -> {id};",
+      self.player, self.tile, self.score, self.depth
+    )
+    .expect("writing to a String can't fail");
+
+    if max_depth > 0 {
+      for child in &self.child_nodes {
+        child.write_dot(dot, &id, next_id, max_depth - 1);
+      }
+    }
+  }
+
   pub fn node_count(&self) -> usize {
     self.child_nodes.iter().map(Node::node_count).sum::<usize>() + 1
   }
@@ -172,6 +425,7 @@ impl Node {
       state,
       valid: true,
       score: 0,
+      tie_key: 0,
       first_score: 0,
       first_score_sqrt: 0,
       player,
@@ -189,7 +443,7 @@ impl Node {
 }
 impl PartialEq for Node {
   fn eq(&self, other: &Self) -> bool {
-    self.score == other.score
+    self.cmp(other) == Ordering::Equal
   }
 }
 impl PartialOrd for Node {
@@ -200,12 +454,14 @@ impl PartialOrd for Node {
 impl Eq for Node {}
 impl Ord for Node {
   fn cmp(&self, other: &Self) -> Ordering {
-    match (self.state, other.state) {
+    let ordering = match (self.state, other.state) {
       (State::Win, State::Win) => self.score.cmp(&other.score),
       (State::Win, _) => Ordering::Greater,
       (_, State::Win) => Ordering::Less,
       (_, _) => self.score.cmp(&other.score),
-    }
+    };
+
+    ordering.then_with(|| other.tie_key.cmp(&self.tie_key))
   }
 }
 impl fmt::Debug for Node {
@@ -244,3 +500,48 @@ impl fmt::Debug for Node {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_principal_moves() {
+    let node = |x, player, state, score, child_nodes| Node {
+      child_nodes,
+      score,
+      ..Node::new(TilePointer { x, y: 0 }, player, state)
+    };
+
+    // the win ends the line, even though there is a child left below it
+    let leftover = node(4, Player::O, State::NotEnd, 0, Vec::new());
+    let win = node(3, Player::X, State::Win, 900, vec![leftover]);
+    let reply = node(2, Player::O, State::NotEnd, -50, vec![win]);
+    let worse = node(1, Player::O, State::NotEnd, -80, Vec::new());
+    let root = node(0, Player::X, State::NotEnd, 30, vec![reply, worse]);
+
+    let tile = |x| TilePointer { x, y: 0 };
+
+    assert_eq!(root.principal_variation(), [tile(0), tile(2), tile(3)]);
+    assert_eq!(
+      root.principal_moves(),
+      [
+        Move {
+          tile: tile(0),
+          score: 30
+        },
+        Move {
+          tile: tile(2),
+          score: -50
+        },
+        Move {
+          tile: tile(3),
+          score: 900
+        },
+      ]
+    );
+
+    let leaf = node(5, Player::X, State::Draw, 0, Vec::new());
+    assert_eq!(leaf.principal_moves(), [leaf.to_move()]);
+  }
+}
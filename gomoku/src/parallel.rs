@@ -0,0 +1,47 @@
+//! Parallel iteration over the search tree
+//!
+//! Uses rayon with the `threads` feature, otherwise sequential iterators
+//! stand in under the same names, so the search code is the same either way.
+
+/// Traits to glob import, like rayon's prelude
+pub(crate) mod prelude {
+  #[cfg(not(feature = "threads"))]
+  pub(crate) use super::sequential::{
+    current_num_threads, IntoParallelIterator, IntoParallelRefMutIterator,
+  };
+  #[cfg(feature = "threads")]
+  pub(crate) use rayon::{
+    current_num_threads,
+    prelude::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator},
+  };
+}
+
+#[cfg(not(feature = "threads"))]
+mod sequential {
+  /// Sequential stand-in for rayon's `IntoParallelIterator`
+  pub trait IntoParallelIterator: IntoIterator + Sized {
+    fn into_par_iter(self) -> Self::IntoIter {
+      self.into_iter()
+    }
+  }
+  impl<T: IntoIterator> IntoParallelIterator for T {}
+
+  /// Sequential stand-in for rayon's `IntoParallelRefMutIterator`
+  pub trait IntoParallelRefMutIterator<'a> {
+    type Iter: Iterator;
+
+    fn par_iter_mut(&'a mut self) -> Self::Iter;
+  }
+  impl<'a, T: 'a> IntoParallelRefMutIterator<'a> for [T] {
+    type Iter = std::slice::IterMut<'a, T>;
+
+    fn par_iter_mut(&'a mut self) -> Self::Iter {
+      self.iter_mut()
+    }
+  }
+
+  /// There is only the current thread.
+  pub fn current_num_threads() -> usize {
+    1
+  }
+}
@@ -3,7 +3,8 @@ use std::{fmt, ops::Not, str::FromStr};
 /// A gomoku player
 ///
 /// Can be X or O
-#[derive(Clone, PartialEq, Eq, Copy)]
+#[derive(Clone, PartialEq, Eq, Hash, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Player {
   #[allow(missing_docs)] // self-explanatory
   X,
@@ -21,6 +22,17 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {}
 
 impl Player {
+  /// Returns both players, X first.
+  pub fn all() -> [Player; 2] {
+    [Player::X, Player::O]
+  }
+
+  /// Returns the other player, same as `!self`.
+  #[must_use]
+  pub fn opponent(self) -> Player {
+    !self
+  }
+
   /// Returns the char representation of the player.
   pub fn char(self) -> char {
     match self {
@@ -29,15 +41,21 @@ impl Player {
     }
   }
 
-  /// Returns the index of the player, used for evaluation structs in minimax.
-  #[deprecated]
-  pub fn index(self) -> usize {
+  /// Returns the index of the player, 0 for X and 1 for O, e.g. for arrays
+  /// with a value for each player.
+  pub fn as_index(self) -> usize {
     match self {
       Player::X => 0,
       Player::O => 1,
     }
   }
 
+  /// Returns the index of the player, used for evaluation structs in minimax.
+  #[deprecated(note = "use `Player::as_index` instead")]
+  pub fn index(self) -> usize {
+    self.as_index()
+  }
+
   /// Tries to convert a char to a player.
   ///
   /// # Errors
@@ -96,3 +114,17 @@ impl FromStr for Player {
     Player::from_string(s)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_all_players() {
+    let players = Player::all();
+
+    assert_eq!(players, [Player::X, Player::O]);
+    assert_eq!(players.map(Player::as_index), [0, 1]);
+    assert_eq!(players.map(Player::opponent), [Player::O, Player::X]);
+  }
+}
@@ -0,0 +1,425 @@
+//! Gomocup (Piskvork) brain protocol
+//!
+//! The manager sends one command per line on stdin and the brain answers on
+//! stdout. [`Brain`] implements the protocol over strings, so a binary only
+//! has to pipe the lines through it. See
+//! <https://plastovicka.github.io/protocl2en.htm> for the full description.
+//!
+//! Coordinates are `x,y` with `x` being the column and `y` the row, both
+//! starting at 0, which is the same as [`TilePointer`].
+
+use std::{fmt, time::Duration};
+
+use crate::{Board, Engine, Player, Rules, SearchOutcome, TilePointer};
+
+/// Time limit used until the manager sends `INFO timeout_turn`
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Part of the turn timeout kept in reserve for the communication
+const TIMEOUT_RESERVE: Duration = Duration::from_millis(100);
+
+/// Bit of `INFO rule` selecting Renju
+const RULE_RENJU: u32 = 4;
+
+/// Bit of `INFO rule` asking for a continuous game, which mostly concerns the
+/// manager. Only the winning line markers it sends in `BOARD` are refused.
+const RULE_CONTINUOUS: u32 = 2;
+
+/// State of a brain talking to a Gomocup manager
+#[derive(Debug)]
+pub struct Brain {
+  board: Option<Board>,
+  /// Stones of the brain, decided by who moves first
+  me: Player,
+  timeout: Duration,
+  rules: Rules,
+  threads: Option<usize>,
+  /// Engine built from the settings above, rebuilt whenever they change, so
+  /// a turn doesn't pay for creating a thread pool
+  engine: Result<Engine, String>,
+  /// Stones received after `BOARD`, until `DONE`
+  pending: Option<Vec<(TilePointer, bool)>>,
+  finished: bool,
+}
+impl Brain {
+  /// Create a brain with no game in progress.
+  pub fn new() -> Self {
+    Brain {
+      board: None,
+      me: Player::X,
+      timeout: DEFAULT_TIMEOUT,
+      rules: Rules::freestyle(),
+      threads: None,
+      engine: build_engine(DEFAULT_TIMEOUT, Rules::freestyle(), None),
+      pending: None,
+      finished: false,
+    }
+  }
+
+  /// Set the number of threads to search with, defaults to all CPUs.
+  #[must_use]
+  pub fn threads(mut self, threads: usize) -> Self {
+    self.threads = Some(threads);
+    self.rebuild_engine();
+    self
+  }
+
+  /// Build the engine again after the time limit, rules or threads changed.
+  fn rebuild_engine(&mut self) {
+    self.engine = build_engine(self.timeout, self.rules, self.threads);
+  }
+
+  /// Check if the manager ended the session with `END`.
+  pub fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  /// Process one line from the manager and return the lines to answer with.
+  pub fn handle(&mut self, line: &str) -> Vec<String> {
+    let line = line.trim();
+
+    if self.pending.is_some() {
+      return self.handle_board_line(line);
+    }
+
+    let (command, args) = line
+      .split_once(' ')
+      .map_or((line, ""), |(command, args)| (command, args.trim()));
+
+    match command.to_ascii_uppercase().as_str() {
+      "" => Vec::new(),
+      "START" => match args.parse() {
+        Ok(size) => self.start(size, size),
+        Err(_) => error(&format!("invalid size {args:?}")),
+      },
+      "RECTSTART" => match parse_pair(args) {
+        Some((width, height)) => self.start(width, height),
+        None => error(&format!("invalid size {args:?}")),
+      },
+      "RESTART" => match &self.board {
+        Some(board) => self.start(board.width(), board.height()),
+        None => error(&"no game in progress"),
+      },
+      "BEGIN" => {
+        self.me = Player::X;
+        self.play()
+      },
+      "TURN" => self.turn(args),
+      "BOARD" => {
+        self.pending = Some(Vec::new());
+        Vec::new()
+      },
+      "TAKEBACK" => self.takeback(args),
+      "INFO" => self.info(args),
+      "ABOUT" => vec![format!(
+        "name=\"gomoku-rust\", version=\"{}\", author=\"Maneren\"",
+        env!("CARGO_PKG_VERSION")
+      )],
+      "END" => {
+        self.finished = true;
+        Vec::new()
+      },
+      _ => vec![format!("UNKNOWN command {command}")],
+    }
+  }
+
+  fn start(&mut self, width: u8, height: u8) -> Vec<String> {
+    match Board::new_rect(width, height) {
+      Ok(board) => {
+        self.board = Some(board);
+        vec!["OK".to_owned()]
+      },
+      Err(_) => error(&format!("unsupported size {width}x{height}")),
+    }
+  }
+
+  fn turn(&mut self, args: &str) -> Vec<String> {
+    let Some(board) = &mut self.board else {
+      return error(&"no game in progress");
+    };
+
+    let Some((x, y)) = parse_pair(args) else {
+      return error(&format!("invalid move {args:?}"));
+    };
+
+    // the opponent moved first
+    if board.empty_count() == board.tiles().len() {
+      self.me = Player::O;
+    }
+
+    if let Err(err) = board.place(TilePointer { x, y }, !self.me) {
+      return error(&err);
+    }
+
+    self.play()
+  }
+
+  fn takeback(&mut self, args: &str) -> Vec<String> {
+    let Some(board) = &mut self.board else {
+      return error(&"no game in progress");
+    };
+
+    let ptr = match parse_pair(args) {
+      Some((x, y)) => TilePointer { x, y },
+      None => return error(&format!("invalid move {args:?}")),
+    };
+
+    match board.get(ptr) {
+      Some(Some(_)) => {
+        board.set_tile(ptr, None);
+        vec!["OK".to_owned()]
+      },
+      _ => error(&format!("no stone at {},{}", ptr.x, ptr.y)),
+    }
+  }
+
+  /// Parse a `x,y,field` line of the `BOARD` command, `DONE` ends it.
+  ///
+  /// Field `1` is an own stone and `2` the opponent's. Field `3` marks a
+  /// winning line in continuous games, which the brain doesn't support.
+  fn handle_board_line(&mut self, line: &str) -> Vec<String> {
+    if !line.eq_ignore_ascii_case("DONE") {
+      let parsed = line.split(',').map(str::trim).collect::<Vec<_>>();
+
+      let stone = match parsed[..] {
+        [_, _, "3"] => return error(&"continuous games are not supported"),
+        [x, y, field] => x.parse().ok().zip(y.parse().ok()).zip(match field {
+          "1" => Some(true),
+          "2" => Some(false),
+          _ => None,
+        }),
+        _ => None,
+      };
+
+      return match stone {
+        Some(((x, y), mine)) => {
+          self
+            .pending
+            .as_mut()
+            .expect("we're reading the board")
+            .push((TilePointer { x, y }, mine));
+          Vec::new()
+        },
+        None => error(&format!("invalid board line {line:?}")),
+      };
+    }
+
+    let stones = self.pending.take().expect("we're reading the board");
+
+    let Some(board) = &self.board else {
+      return error(&"no game in progress");
+    };
+
+    // the side to move has as many stones as the opponent only if it moved
+    // first
+    let mine = stones.iter().filter(|(_, mine)| *mine).count();
+    self.me = if 2 * mine == stones.len() {
+      Player::X
+    } else {
+      Player::O
+    };
+
+    let mut board =
+      Board::new_rect(board.width(), board.height()).expect("the size was accepted by start");
+
+    for (ptr, mine) in stones {
+      let player = if mine { self.me } else { !self.me };
+
+      if let Err(err) = board.place(ptr, player) {
+        return error(&err);
+      }
+    }
+
+    self.board = Some(board);
+    self.play()
+  }
+
+  /// Apply an `INFO` setting, answering only if it can't be followed.
+  fn info(&mut self, args: &str) -> Vec<String> {
+    let (key, value) = args.split_once(' ').unwrap_or((args, ""));
+
+    match key {
+      "timeout_turn" => {
+        if let Ok(millis) = value.trim().parse::<u64>() {
+          // zero means play as fast as possible
+          self.timeout = Duration::from_millis(millis)
+            .saturating_sub(TIMEOUT_RESERVE)
+            .max(Duration::from_millis(1));
+          self.rebuild_engine();
+        }
+      },
+      "rule" => {
+        if let Ok(rule) = value.trim().parse::<u32>() {
+          // exact five (1) and caro (8) have no matching rules, playing
+          // freestyle instead would aim for overlines that don't win
+          if rule & !(RULE_RENJU | RULE_CONTINUOUS) != 0 {
+            return error(&format!("unsupported rule {rule}"));
+          }
+
+          self.rules = if rule & RULE_RENJU == 0 {
+            Rules::freestyle()
+          } else {
+            Rules::renju()
+          };
+          self.rebuild_engine();
+        }
+      },
+      // the other keys are informative only
+      _ => {},
+    }
+
+    Vec::new()
+  }
+
+  /// Search for a move, play it and return it.
+  fn play(&mut self) -> Vec<String> {
+    let Some(board) = &mut self.board else {
+      return error(&"no game in progress");
+    };
+
+    let engine = match &self.engine {
+      Ok(engine) => engine,
+      Err(err) => return error(err),
+    };
+
+    match engine.decide(board, self.me) {
+      Ok(SearchOutcome {
+        best: Some(move_), ..
+      }) => vec![format!("{},{}", move_.tile.x, move_.tile.y)],
+      Ok(_) => error(&"no empty tiles left"),
+      Err(err) => error(&err),
+    }
+  }
+}
+impl Default for Brain {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+fn build_engine(timeout: Duration, rules: Rules, threads: Option<usize>) -> Result<Engine, String> {
+  let mut builder = Engine::builder().time_limit(timeout).rules(rules);
+
+  if let Some(threads) = threads {
+    builder = builder.threads(threads);
+  }
+
+  builder.build().map_err(|err| err.to_string())
+}
+
+fn error(message: &impl fmt::Display) -> Vec<String> {
+  vec![format!("ERROR {message}")]
+}
+
+/// Parse `a,b` into two numbers.
+fn parse_pair(input: &str) -> Option<(u8, u8)> {
+  let (a, b) = input.split_once(',')?;
+  Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn brain() -> Brain {
+    let mut brain = Brain::new().threads(1);
+    brain.handle("INFO timeout_turn 300");
+    brain
+  }
+
+  fn parse_move(output: &[String]) -> TilePointer {
+    let [line] = output else {
+      panic!("expected a single line, got {output:?}");
+    };
+
+    let (x, y) = parse_pair(line).expect("output is a move");
+    TilePointer { x, y }
+  }
+
+  #[test]
+  fn test_start_and_turn() {
+    let mut brain = brain();
+
+    assert_eq!(brain.handle("START 15"), vec!["OK"]);
+    assert!(brain.handle("START 5")[0].starts_with("ERROR"));
+    assert!(brain.handle("FOO")[0].starts_with("UNKNOWN"));
+
+    let reply = parse_move(&brain.handle("TURN 7,7"));
+
+    let board = brain.board.as_ref().unwrap();
+    assert_eq!(*board.get_tile(TilePointer { x: 7, y: 7 }), Some(Player::X));
+    assert_eq!(*board.get_tile(reply), Some(Player::O));
+
+    assert!(brain.handle("TURN 7,7")[0].starts_with("ERROR"));
+
+    brain.handle("END");
+    assert!(brain.is_finished());
+  }
+
+  #[test]
+  fn test_board_coordinates() {
+    let mut brain = brain();
+    brain.handle("START 15");
+
+    // own vertical four from (3,1) to (3,4), the opponent's stones elsewhere
+    let lines = [
+      "BOARD", "3,1,1", "10,10,2", "3,2,1", "11,10,2", "3,3,1", "12,0,2", "3,4,1", "0,14,2",
+    ];
+    for line in lines {
+      assert!(brain.handle(line).is_empty());
+    }
+
+    // only continuous games mark winning lines, and other fields aren't stones
+    for line in ["6,6,3", "6,6,0", "6,6,4"] {
+      assert!(brain.handle(line)[0].starts_with("ERROR"));
+    }
+
+    let reply = parse_move(&brain.handle("DONE"));
+
+    assert!([TilePointer { x: 3, y: 0 }, TilePointer { x: 3, y: 5 }].contains(&reply));
+    assert_eq!(brain.me, Player::X);
+  }
+
+  #[test]
+  fn test_info_rule() {
+    let mut brain = brain();
+
+    // a continuous game is up to the manager
+    assert!(brain.handle("INFO rule 2").is_empty());
+    assert_eq!(brain.rules, Rules::freestyle());
+
+    assert!(brain.handle("INFO rule 4").is_empty());
+    assert_eq!(brain.rules, Rules::renju());
+    assert_eq!(brain.engine.as_ref().unwrap().rules(), Rules::renju());
+
+    // exact five isn't supported, so the brain says so and keeps its rules
+    assert!(brain.handle("INFO rule 1")[0].starts_with("ERROR"));
+    assert_eq!(brain.rules, Rules::renju());
+
+    assert!(brain.handle("INFO timeout_turn 1100").is_empty());
+    assert_eq!(
+      brain.engine.as_ref().unwrap().time_limit(),
+      Duration::from_secs(1)
+    );
+  }
+
+  #[test]
+  fn test_begin() {
+    let mut brain = brain();
+    brain.handle("START 15");
+
+    let reply = parse_move(&brain.handle("BEGIN"));
+
+    assert_eq!(brain.me, Player::X);
+    assert_eq!(
+      *brain.board.as_ref().unwrap().get_tile(reply),
+      Some(Player::X)
+    );
+
+    assert_eq!(
+      brain.handle(&format!("TAKEBACK {},{}", reply.x, reply.y)),
+      vec!["OK"]
+    );
+    assert_eq!(brain.board.as_ref().unwrap().empty_count(), 15 * 15);
+  }
+}
@@ -0,0 +1,252 @@
+use crate::{Board, Player, Tile, TilePointer};
+
+/// How far to look in each direction from the move
+const REACH: i8 = 5;
+
+/// Tiles of a line centered on a move, `None` is outside of the board
+type Line = [Option<Tile>; 2 * REACH as usize + 1];
+
+/// Index of the move in a [`Line`]
+const CENTER: usize = REACH as usize;
+
+/// Rules of the game the engine plays by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Rules {
+  /// Rule variant in effect
+  pub variant: Variant,
+  /// Whether a full board without a five is a draw
+  ///
+  /// [`Board::is_full`] only reports that there are no empty tiles, what that
+  /// means is up to this. When `false`, a full board is treated like any
+  /// other position without a legal move, which the player on turn loses, so
+  /// filling the last tile wins. Meant for variants played on boards large
+  /// enough to never fill up. Defaults to `true`.
+  pub can_draw: bool,
+}
+impl Rules {
+  /// Create rules for the given variant.
+  pub fn new(variant: Variant) -> Self {
+    Rules {
+      variant,
+      can_draw: true,
+    }
+  }
+
+  /// Set whether a full board is a draw, see [`Rules::can_draw`].
+  #[must_use]
+  pub fn with_draws(mut self, can_draw: bool) -> Self {
+    self.can_draw = can_draw;
+    self
+  }
+
+  /// Freestyle gomoku: five or more in a row wins and no moves are
+  /// restricted.
+  pub fn freestyle() -> Self {
+    Self::new(Variant::Freestyle)
+  }
+
+  /// Renju: black (X) must not make a double three, a double four or an
+  /// overline, unless the move also makes exactly five.
+  pub fn renju() -> Self {
+    Self::new(Variant::Renju)
+  }
+
+  /// Check if a line of six or more stones of `player` wins.
+  ///
+  /// Under Renju only white (O) wins with an overline.
+  pub fn allows_overline(&self, player: Player) -> bool {
+    match self.variant {
+      Variant::Freestyle => true,
+      Variant::Renju => player == Player::O,
+    }
+  }
+
+  /// Check if `player` is not allowed to play at the empty tile `ptr`.
+  ///
+  /// Whether the tiles completing a three are themselves forbidden is not
+  /// considered, so a few complex double threes are detected even though
+  /// full Renju rules would allow them.
+  pub fn is_forbidden(&self, board: &Board, ptr: TilePointer, player: Player) -> bool {
+    self.is_forbidden_by(
+      (board.width(), board.height()),
+      |tile| *board.get_tile(tile),
+      ptr,
+      player,
+    )
+  }
+
+  /// Same as [`Rules::is_forbidden`], but with tiles given by `tile_at` on a
+  /// board of the given dimensions, so the solvers can check their own copy
+  /// of the board.
+  pub(crate) fn is_forbidden_by(
+    self,
+    dimensions: (u8, u8),
+    tile_at: impl Fn(TilePointer) -> Tile,
+    ptr: TilePointer,
+    player: Player,
+  ) -> bool {
+    match self.variant {
+      Variant::Freestyle => false,
+      Variant::Renju => {
+        player == Player::X && is_forbidden_renju(dimensions, &tile_at, ptr, player)
+      },
+    }
+  }
+}
+impl Default for Rules {
+  fn default() -> Self {
+    Self::freestyle()
+  }
+}
+
+fn is_forbidden_renju(
+  dimensions: (u8, u8),
+  tile_at: &impl Fn(TilePointer) -> Tile,
+  ptr: TilePointer,
+  player: Player,
+) -> bool {
+  let lines = TilePointer::DIRECTIONS.map(|dir| line(dimensions, tile_at, ptr, dir, player));
+
+  if lines
+    .iter()
+    .any(|line| run_length(line, CENTER, player) == 5)
+  {
+    return false;
+  }
+
+  let overline = lines
+    .iter()
+    .any(|line| run_length(line, CENTER, player) > 5);
+  let fours = lines.iter().filter(|line| is_four(line, player)).count();
+  let threes = lines
+    .iter()
+    .filter(|line| is_open_three(line, player))
+    .count();
+
+  overline || fours >= 2 || threes >= 2
+}
+
+/// Get the line through `ptr` in the given direction with `player`'s stone
+/// placed at `ptr`.
+fn line(
+  (width, height): (u8, u8),
+  tile_at: &impl Fn(TilePointer) -> Tile,
+  ptr: TilePointer,
+  (dx, dy): (i8, i8),
+  player: Player,
+) -> Line {
+  let mut line = [None; 2 * CENTER + 1];
+
+  for (i, steps) in (-REACH..=REACH).enumerate() {
+    if let Some(tile) = ptr.offset(dx * steps, dy * steps, width, height) {
+      line[i] = Some(tile_at(tile));
+    }
+  }
+
+  line[CENTER] = Some(Some(player));
+  line
+}
+
+/// Length of the unbroken run of `player`'s stones going through `index`.
+fn run_length(line: &Line, index: usize, player: Player) -> usize {
+  let is_player = |tile: &&Option<Tile>| **tile == Some(Some(player));
+
+  let before = line[..index].iter().rev().take_while(is_player).count();
+  let after = line[index + 1..].iter().take_while(is_player).count();
+
+  before + 1 + after
+}
+
+/// Get indices of the empty tiles where `player` would make exactly five
+/// including the move.
+fn five_points(line: &Line, player: Player) -> Vec<usize> {
+  (0..line.len())
+    .filter(|&i| line[i] == Some(None))
+    .filter(|&i| {
+      let mut line = *line;
+      line[i] = Some(Some(player));
+
+      run_length(&line, i, player) == 5 && run_length(&line, CENTER, player) == 5
+    })
+    .collect()
+}
+
+/// Check if the move makes a four, i.e. one more stone makes exactly five.
+fn is_four(line: &Line, player: Player) -> bool {
+  !five_points(line, player).is_empty()
+}
+
+/// Check if the move makes an open three, i.e. one more stone makes a
+/// straight four, which can be completed to five at both ends.
+fn is_open_three(line: &Line, player: Player) -> bool {
+  (0..line.len()).filter(|&i| line[i] == Some(None)).any(|i| {
+    let mut line = *line;
+    line[i] = Some(Some(player));
+
+    run_length(&line, CENTER, player) == 4 && five_points(&line, player).len() == 2
+  })
+}
+
+/// Gomoku rule variants
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Variant {
+  /// Five or more in a row wins, no restricted moves
+  #[default]
+  Freestyle,
+  /// Black (X) can't make double threes, double fours and overlines, see
+  /// [`Rules::renju`]
+  Renju,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_renju_fours_and_overline() {
+    let mut board = Board::new_empty(15).unwrap();
+    let ptr = |x, y| TilePointer { x, y };
+
+    let stones = [
+      // xxx-xx
+      (0, 0),
+      (1, 0),
+      (2, 0),
+      (4, 0),
+      (5, 0),
+      // xxxx vertical
+      (4, 2),
+      (4, 3),
+      (4, 4),
+      (4, 5),
+      // xxx- horizontal and -xxx vertical meeting at (4, 10)
+      (1, 10),
+      (2, 10),
+      (3, 10),
+      (4, 11),
+      (4, 12),
+      (4, 13),
+    ];
+    for (x, y) in stones {
+      board.set_tile(ptr(x, y), Some(Player::X));
+    }
+
+    let rules = Rules::renju();
+
+    // overline
+    assert!(rules.is_forbidden(&board, ptr(3, 0), Player::X));
+    assert!(!rules.is_forbidden(&board, ptr(3, 0), Player::O));
+    assert!(!Rules::freestyle().is_forbidden(&board, ptr(3, 0), Player::X));
+
+    // exactly five
+    assert!(!rules.is_forbidden(&board, ptr(4, 6), Player::X));
+
+    // double four
+    assert!(rules.is_forbidden(&board, ptr(4, 10), Player::X));
+
+    // a single four
+    assert!(!rules.is_forbidden(&board, ptr(0, 10), Player::X));
+  }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use crate::{
+  board, Board, Engine, GameState, GomokuError, Player, SearchOutcome, Status, TilePointer,
+};
+
+/// Games played against an [`Engine`]
+///
+/// Keeps the game in progress together with the engine, whose opening book
+/// is used for every game, and a cache of positions the engine already
+/// searched. Taking back a move and replaying it returns the cached answer
+/// instead of searching again.
+///
+/// The cache holds one entry per engine move, so it never grows beyond the
+/// number of tiles on the board. It only lives for a single game and is
+/// cleared by [`Session::new_game`], as answers found with one time limit or
+/// rule set aren't worth keeping around.
+pub struct Session {
+  engine: Engine,
+  game: GameState,
+  /// Outcome of every searched position, keyed by its
+  /// [Zobrist hash](Board::zobrist) and the player on turn
+  cache: HashMap<(u64, Player), SearchOutcome>,
+}
+impl Session {
+  /// Start a session with an empty board of the given size. X moves first
+  /// and the game is played by the engine's rules.
+  ///
+  /// # Errors
+  /// Returns an error if the size is below [`Board::MIN_SIZE`].
+  pub fn new(engine: Engine, size: u8) -> Result<Self, board::Error> {
+    Ok(Session {
+      game: GameState::new(size)?.with_rules(engine.rules()),
+      engine,
+      cache: HashMap::new(),
+    })
+  }
+
+  /// Start a new game on an empty board, forgetting the cached positions.
+  ///
+  /// # Errors
+  /// Returns an error if the size is below [`Board::MIN_SIZE`]. The current
+  /// game is kept in that case.
+  pub fn new_game(&mut self, size: u8) -> Result<(), board::Error> {
+    self.game = GameState::new(size)?.with_rules(self.engine.rules());
+    self.cache.clear();
+    Ok(())
+  }
+
+  /// Play the opponent's move and answer it with the engine's move.
+  ///
+  /// # Errors
+  /// Returns [`GomokuError::IllegalMove`] if the move can't be played, the
+  /// game is left unchanged in that case. If the move wins the game, it stays
+  /// played and [`GomokuError::GameEnd`] is returned.
+  pub fn play_and_respond(
+    &mut self,
+    human_move: TilePointer,
+  ) -> Result<SearchOutcome, GomokuError> {
+    self
+      .game
+      .play(human_move)
+      .map_err(GomokuError::IllegalMove)?;
+
+    self.respond()
+  }
+
+  /// Let the engine play for the player on turn, e.g. to open the game.
+  ///
+  /// # Errors
+  /// Returns [`GomokuError::GameEnd`] if the game has already been won, or
+  /// any other error from [`Engine::decide`]. A drawn game has no move to
+  /// play, same as in [`Engine::decide`].
+  pub fn respond(&mut self) -> Result<SearchOutcome, GomokuError> {
+    if let Status::Win(winner) = self.game.status() {
+      return Err(GomokuError::GameEnd { winner });
+    }
+
+    let player = self.game.to_move();
+    let key = (self.game.board().zobrist(), player);
+
+    let outcome = if let Some(outcome) = self.cache.get(&key) {
+      outcome.clone()
+    } else {
+      let mut board = self.game.board().clone();
+      let outcome = self.engine.decide(&mut board, player)?;
+
+      self.cache.insert(key, outcome.clone());
+      outcome
+    };
+
+    if let Some(best) = outcome.best {
+      self
+        .game
+        .play(best.tile)
+        .map_err(GomokuError::IllegalMove)?;
+    }
+
+    Ok(outcome)
+  }
+
+  /// Take back the last move, see [`GameState::undo`].
+  pub fn undo(&mut self) -> Option<TilePointer> {
+    self.game.undo()
+  }
+
+  /// Get the game in progress.
+  pub fn game(&self) -> &GameState {
+    &self.game
+  }
+
+  /// Get the board of the game in progress.
+  pub fn board(&self) -> &Board {
+    self.game.board()
+  }
+
+  /// Get the engine.
+  pub fn engine(&self) -> &Engine {
+    &self.engine
+  }
+
+  /// Get the number of positions in the cache.
+  pub fn cached_positions(&self) -> usize {
+    self.cache.len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+  use crate::{PlaceError, Rules};
+
+  #[test]
+  fn test_session() {
+    let engine = Engine::builder()
+      .time_limit(Duration::from_millis(100))
+      .threads(1)
+      .build()
+      .unwrap();
+    let mut session = Session::new(engine, 15).unwrap();
+
+    let center = TilePointer { x: 7, y: 7 };
+    let first = session.play_and_respond(center).unwrap().best.unwrap();
+
+    assert_eq!(session.game().ply(), 2);
+    assert_eq!(*session.board().get_tile(first.tile), Some(Player::O));
+    assert_eq!(session.cached_positions(), 1);
+
+    assert!(matches!(
+      session.play_and_respond(center),
+      Err(GomokuError::IllegalMove(_))
+    ));
+    assert_eq!(session.game().ply(), 2);
+
+    // replaying the same position hits the cache
+    session.undo();
+    session.undo();
+    let again = session.play_and_respond(center).unwrap().best.unwrap();
+    assert_eq!(again, first);
+    assert_eq!(session.cached_positions(), 1);
+
+    session.new_game(9).unwrap();
+    assert_eq!(session.cached_positions(), 0);
+    assert_eq!(session.board().width(), 9);
+  }
+
+  #[test]
+  fn test_session_rules() {
+    let engine = Engine::builder()
+      .time_limit(Duration::from_millis(100))
+      .threads(1)
+      .rules(Rules::renju())
+      .build()
+      .unwrap();
+    let mut session = Session::new(engine, 15).unwrap();
+
+    assert_eq!(session.game().rules(), Rules::renju());
+
+    // X's four in a row with an extra stone one tile after it
+    for x in [0, 1, 2, 3, 5] {
+      session.game.play(TilePointer { x, y: 0 }).unwrap();
+      session.game.play(TilePointer { x, y: 14 }).unwrap();
+    }
+
+    let overline = TilePointer { x: 4, y: 0 };
+    assert!(matches!(
+      session.play_and_respond(overline),
+      Err(GomokuError::IllegalMove(PlaceError::Forbidden(tile))) if tile == overline
+    ));
+    assert_eq!(session.game().ply(), 10);
+  }
+}
@@ -0,0 +1,124 @@
+use crate::{node::Node, state::State, utils::splitmix64};
+
+/// Strength of the engine
+///
+/// Lower levels search shallower, consider fewer moves and sometimes play one
+/// of the next best moves instead of the best one:
+///
+/// | Skill      | Max depth | Beam | Mistake chance | Picks from |
+/// |------------|-----------|------|----------------|------------|
+/// | `Beginner` | 2         | 1.0  | 50 %           | top 4      |
+/// | `Easy`     | 3         | 1.0  | 30 %           | top 3      |
+/// | `Medium`   | 5         | 1.5  | 10 %           | top 2      |
+/// | `Hard`     | 8         | 2.0  | never          |            |
+/// | `Max`      | unlimited | 2.0  | never          |            |
+///
+/// Beam is the factor of `sqrt(n)` moves kept after every depth. Depth 2 is
+/// enough to see the opponent's immediate wins and mistakes never pick a move
+/// that is known to lose, so even the lowest level blocks obvious threats.
+/// Winning moves are always played. Mistakes are drawn from the seed set by
+/// [`EngineBuilder::skill_seed`](crate::EngineBuilder::skill_seed), so they
+/// are reproducible.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Skill {
+  #[allow(missing_docs)] // self-explanatory
+  Beginner,
+  #[allow(missing_docs)] // self-explanatory
+  Easy,
+  #[allow(missing_docs)] // self-explanatory
+  Medium,
+  #[allow(missing_docs)] // self-explanatory
+  Hard,
+  /// Full strength
+  #[default]
+  Max,
+}
+impl Skill {
+  /// Maximum depth to search to, `None` if unlimited.
+  pub(crate) fn max_depth(self) -> Option<u8> {
+    match self {
+      Skill::Beginner => Some(2),
+      Skill::Easy => Some(3),
+      Skill::Medium => Some(5),
+      Skill::Hard => Some(8),
+      Skill::Max => None,
+    }
+  }
+
+  /// Factor of `sqrt(n)` top-level moves kept after every depth.
+  pub(crate) fn beam(self) -> f32 {
+    match self {
+      Skill::Beginner | Skill::Easy => 1.0,
+      Skill::Medium => 1.5,
+      Skill::Hard | Skill::Max => 2.0,
+    }
+  }
+
+  /// Chance of playing a worse move and how many best moves to pick from.
+  fn mistakes(self) -> (f64, usize) {
+    match self {
+      Skill::Beginner => (0.5, 4),
+      Skill::Easy => (0.3, 3),
+      Skill::Medium => (0.1, 2),
+      Skill::Hard | Skill::Max => (0.0, 1),
+    }
+  }
+
+  /// Choose the node to play from nodes sorted best first, the mistakes are
+  /// drawn from `seed`.
+  pub(crate) fn choose(self, nodes: &[Node], seed: u64) -> &Node {
+    let best = nodes.first().expect("we never remove all nodes");
+
+    let (chance, count) = self.mistakes();
+
+    if count <= 1 || best.state != State::NotEnd {
+      return best;
+    }
+
+    let random = splitmix64(seed);
+
+    // top 53 bits make a uniform float in [0, 1)
+    if ((random >> 11) as f64 / (1_u64 << 53) as f64) >= chance {
+      return best;
+    }
+
+    let candidates = nodes
+      .iter()
+      .take(count)
+      .filter(|node| node.state == State::NotEnd)
+      .collect::<Vec<_>>();
+
+    candidates[splitmix64(random) as usize % candidates.len()]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Player, TilePointer};
+
+  #[test]
+  fn test_mistakes_are_seeded() {
+    let nodes = (0..6)
+      .map(|x| Node::new(TilePointer { x, y: 0 }, Player::X, State::NotEnd))
+      .collect::<Vec<_>>();
+
+    let chosen = |skill: Skill, seed| skill.choose(&nodes, seed).to_move().tile.x;
+
+    let picks = (0..64)
+      .map(|seed| chosen(Skill::Beginner, seed))
+      .collect::<Vec<_>>();
+
+    // the same seed makes the same choice
+    for (seed, &pick) in (0..64).zip(&picks) {
+      assert_eq!(chosen(Skill::Beginner, seed), pick);
+    }
+
+    // some seeds make a mistake, which stays among the top 4
+    assert!(picks.contains(&0));
+    assert!(picks.iter().any(|&pick| pick != 0));
+    assert!(picks.iter().all(|&pick| pick < 4));
+
+    assert!((0..64).all(|seed| chosen(Skill::Max, seed) == 0));
+  }
+}
@@ -0,0 +1,530 @@
+use crate::{Board, Player, Rules, Tile, TilePointer};
+
+/// Lightweight copy of the board used by the solvers
+///
+/// Placing stones doesn't update any evaluation caches, so make/unmake is
+/// as cheap as possible.
+struct Grid {
+  width: u8,
+  height: u8,
+  tiles: Vec<Tile>,
+  /// Rules deciding which fives win and which moves are allowed
+  rules: Rules,
+}
+impl Grid {
+  fn new(board: &Board) -> Self {
+    Self::with_rules(board, Rules::freestyle())
+  }
+
+  fn with_rules(board: &Board, rules: Rules) -> Self {
+    Grid {
+      width: board.width(),
+      height: board.height(),
+      tiles: board.tiles().to_vec(),
+      rules,
+    }
+  }
+
+  fn index(&self, ptr: TilePointer) -> usize {
+    usize::from(self.width) * usize::from(ptr.y) + usize::from(ptr.x)
+  }
+
+  fn get(&self, ptr: TilePointer) -> Tile {
+    self.tiles[self.index(ptr)]
+  }
+
+  fn set(&mut self, ptr: TilePointer, value: Tile) {
+    let index = self.index(ptr);
+    self.tiles[index] = value;
+  }
+
+  /// Move `steps` times in the given direction, if it stays on the board.
+  fn offset(&self, ptr: TilePointer, (dx, dy): (i8, i8), steps: i8) -> Option<TilePointer> {
+    ptr.offset(dx * steps, dy * steps, self.width, self.height)
+  }
+
+  fn all_tiles(&self) -> impl Iterator<Item = TilePointer> {
+    let width = self.width;
+    (0..self.height).flat_map(move |y| (0..width).map(move |x| TilePointer { x, y }))
+  }
+
+  fn empty_tiles(&self) -> Vec<TilePointer> {
+    self
+      .all_tiles()
+      .filter(|&ptr| self.get(ptr).is_none())
+      .collect()
+  }
+
+  /// Check if placing `player` at the empty tile `ptr` completes a five
+  /// winning under the rules.
+  fn makes_five(&self, ptr: TilePointer, player: Player) -> bool {
+    TilePointer::DIRECTIONS.into_iter().any(|dir| {
+      // one step further than a five, to tell an overline apart
+      let count = |sign: i8| {
+        (1..6)
+          .map_while(|steps| self.offset(ptr, dir, sign * steps))
+          .take_while(|&tile| self.get(tile) == Some(player))
+          .count()
+      };
+
+      match 1 + count(1) + count(-1) {
+        5 => true,
+        run => run > 5 && self.rules.allows_overline(player),
+      }
+    })
+  }
+
+  /// Check if the rules forbid `player` to play at the empty tile `ptr`.
+  fn is_forbidden(&self, ptr: TilePointer, player: Player) -> bool {
+    self.rules.is_forbidden_by(
+      (self.width, self.height),
+      |tile| self.get(tile),
+      ptr,
+      player,
+    )
+  }
+
+  /// Get all tiles where `player` would complete a five.
+  fn five_points(&self, player: Player) -> Vec<TilePointer> {
+    self
+      .empty_tiles()
+      .into_iter()
+      .filter(|&ptr| self.makes_five(ptr, player))
+      .collect()
+  }
+
+  /// Get empty tiles that can still become part of a five within `plies`
+  /// moves, `mover` making the first one.
+  ///
+  /// With freestyle rules a stone anywhere else neither helps nor hurts
+  /// anybody within the horizon, so such a move is as good as passing. The
+  /// tiles are sorted by the number of stones missing to the closest five.
+  fn relevant_tiles(&self, mover: Player, plies: u8) -> Vec<TilePointer> {
+    let budget = |player: Player| {
+      if player == mover {
+        plies.div_ceil(2)
+      } else {
+        plies / 2
+      }
+    };
+
+    let mut missing = vec![u8::MAX; self.tiles.len()];
+
+    for start in self.all_tiles() {
+      for dir in TilePointer::DIRECTIONS {
+        let Some(window) = (0..5)
+          .map(|steps| self.offset(start, dir, steps))
+          .collect::<Option<Vec<_>>>()
+        else {
+          continue;
+        };
+
+        for player in [Player::X, Player::O] {
+          if window.iter().any(|&tile| self.get(tile) == Some(!player)) {
+            continue;
+          }
+
+          let empty = window
+            .iter()
+            .filter(|&&tile| self.get(tile).is_none())
+            .count() as u8;
+
+          if empty == 0 || empty > budget(player) {
+            continue;
+          }
+
+          for &tile in &window {
+            let index = self.index(tile);
+            missing[index] = missing[index].min(empty);
+          }
+        }
+      }
+    }
+
+    let mut tiles = self
+      .empty_tiles()
+      .into_iter()
+      .filter(|&ptr| missing[self.index(ptr)] != u8::MAX)
+      .collect::<Vec<_>>();
+    tiles.sort_by_key(|&ptr| missing[self.index(ptr)]);
+
+    tiles
+  }
+
+  /// Get tiles where `player` would complete a five using the stone at `ptr`.
+  fn five_points_through(&self, ptr: TilePointer, player: Player) -> Vec<TilePointer> {
+    let mut points = Vec::new();
+
+    for dir in TilePointer::DIRECTIONS {
+      for steps in (-4..=4).filter(|&steps| steps != 0) {
+        let Some(tile) = self.offset(ptr, dir, steps) else {
+          continue;
+        };
+
+        if self.get(tile).is_none() && self.makes_five(tile, player) && !points.contains(&tile) {
+          points.push(tile);
+        }
+      }
+    }
+
+    points
+  }
+}
+
+/// Search for a victory by continuous fours (VCF).
+///
+/// Considers only moves creating a four, so the opponent's reply is always
+/// forced, and returns the winning line if one exists. The line alternates
+/// between `player` and the opponent and ends with the move completing five.
+///
+/// `max_depth` is the maximum number of moves `player` makes, including the
+/// final one.
+///
+/// The search is conservative: when the opponent already has a four, only an
+/// immediate five is accepted, so a returned line is always a forced win.
+pub fn find_vcf(board: &Board, player: Player, max_depth: u8) -> Option<Vec<TilePointer>> {
+  vcf(&mut Grid::new(board), player, max_depth)
+}
+
+fn vcf(grid: &mut Grid, player: Player, depth: u8) -> Option<Vec<TilePointer>> {
+  if depth == 0 {
+    return None;
+  }
+
+  if let Some(&tile) = grid.five_points(player).first() {
+    return Some(vec![tile]);
+  }
+
+  // the opponent would complete their five instead of blocking our four
+  if depth == 1 || !grid.five_points(!player).is_empty() {
+    return None;
+  }
+
+  for tile in grid.empty_tiles() {
+    if grid.is_forbidden(tile, player) {
+      continue;
+    }
+
+    grid.set(tile, Some(player));
+
+    let line = match grid.five_points_through(tile, player)[..] {
+      [] => None,
+      [block] => {
+        grid.set(block, Some(!player));
+        let line = vcf(grid, player, depth - 1);
+        grid.set(block, None);
+
+        line.map(|rest| [vec![tile, block], rest].concat())
+      },
+      // the opponent can block only one of the threats
+      [block, win, ..] => Some(vec![tile, block, win]),
+    };
+
+    grid.set(tile, None);
+
+    if line.is_some() {
+      return line;
+    }
+  }
+
+  None
+}
+
+/// Prove a forced win for `player` within `max_ply` plies.
+///
+/// Plies count the moves of both players, so a win in 3 is a move, any reply
+/// and the winning move. Unlike the engine search, every reply of the
+/// opponent that could matter is checked, so `None` means that there really
+/// is no forced win within the bound. Freestyle rules are assumed, so
+/// overlines count as fives.
+///
+/// Returns the shortest winning line, alternating between `player` and the
+/// opponent's most stubborn defence and ending with the move completing five.
+pub fn solve(board: &Board, player: Player, max_ply: u8) -> Option<Vec<TilePointer>> {
+  let mut grid = Grid::new(board);
+
+  (1..=max_ply)
+    .step_by(2)
+    .find_map(|plies| attack(&mut grid, player, plies))
+}
+
+/// Find a move of `player` winning within `plies` against any defence.
+fn attack(grid: &mut Grid, player: Player, plies: u8) -> Option<Vec<TilePointer>> {
+  if plies == 0 {
+    return None;
+  }
+
+  let tiles = grid.relevant_tiles(player, plies);
+
+  if let Some(&tile) = tiles.iter().find(|&&tile| grid.makes_five(tile, player)) {
+    return Some(vec![tile]);
+  }
+
+  if plies < 3 {
+    return None;
+  }
+
+  tiles.into_iter().find_map(|tile| {
+    grid.set(tile, Some(player));
+    let line = defend(grid, player, plies - 1);
+    grid.set(tile, None);
+
+    line.map(|rest| [vec![tile], rest].concat())
+  })
+}
+
+/// Check every defence of the opponent of `player` with `plies` left.
+///
+/// Returns the longest line `player` needs to win, if all defences fail.
+fn defend(grid: &mut Grid, player: Player, plies: u8) -> Option<Vec<TilePointer>> {
+  let defender = !player;
+  let mut tiles = grid.relevant_tiles(defender, plies);
+
+  if tiles.iter().any(|&tile| grid.makes_five(tile, defender)) {
+    return None;
+  }
+
+  // nothing matters anymore, so let the opponent pass with any move
+  if tiles.is_empty() {
+    tiles.extend(grid.empty_tiles().first());
+  }
+
+  let mut longest: Option<Vec<TilePointer>> = None;
+
+  for tile in tiles {
+    grid.set(tile, Some(defender));
+    let line = attack(grid, player, plies - 1);
+    grid.set(tile, None);
+
+    let rest = line?;
+
+    if longest
+      .as_ref()
+      .is_none_or(|line| rest.len() + 1 > line.len())
+    {
+      longest = Some([vec![tile], rest].concat());
+    }
+  }
+
+  longest
+}
+
+/// Check if `player` placing a stone at the empty tile `ptr` creates a four,
+/// i.e. a line of five with four of their stones and one empty tile.
+///
+/// Works directly on the board, so it's cheap enough to run for every node.
+fn creates_four(board: &Board, ptr: TilePointer, player: Player) -> bool {
+  let (width, height) = (board.width(), board.height());
+
+  TilePointer::DIRECTIONS.into_iter().any(|(dx, dy)| {
+    (-4..=0).any(|start| {
+      let mut stones = 0;
+      let mut empty = 0;
+
+      for steps in start..start + 5 {
+        let Some(tile) = ptr.offset(dx * steps, dy * steps, width, height) else {
+          return false;
+        };
+
+        match board.get_tile(tile) {
+          _ if steps == 0 => stones += 1,
+          Some(owner) if *owner == player => stones += 1,
+          Some(_) => return false,
+          None => empty += 1,
+        }
+      }
+
+      stones == 4 && empty == 1
+    })
+  })
+}
+
+/// Resolve forcing moves at the search horizon.
+///
+/// If `player` placing a stone at the empty tile `ptr` creates a four, the
+/// opponent's reply is forced, so the fours are followed until the position
+/// is quiet. Returns the number of moves of both players until the five,
+/// including the one at `ptr`, if that leads to a forced win.
+///
+/// `max_depth` is the maximum number of further moves `player` makes. Only
+/// fives winning under the `rules` count and the continuing fours avoid
+/// forbidden tiles.
+pub(crate) fn quiescence(
+  board: &Board,
+  ptr: TilePointer,
+  player: Player,
+  max_depth: u8,
+  rules: Rules,
+) -> Option<u8> {
+  if !creates_four(board, ptr, player) {
+    return None;
+  }
+
+  let mut grid = Grid::with_rules(board, rules);
+  grid.set(ptr, Some(player));
+
+  // the opponent completes their own five instead of blocking
+  if !grid.five_points(!player).is_empty() {
+    return None;
+  }
+
+  match grid.five_points_through(ptr, player)[..] {
+    [] => None,
+    [block] => {
+      grid.set(block, Some(!player));
+      vcf(&mut grid, player, max_depth).map(|line| line.len() as u8 + 2)
+    },
+    // the opponent can block only one of the threats
+    [_, _, ..] => Some(3),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+
+  const VCF_BOARD: &str = "---------
+---------
+---------
+---------
+oxxx-----
+----x----
+----x----
+---------
+---------";
+
+  /// Check that the line is a forced win for `player`
+  fn assert_forced_win(board: &Board, player: Player, line: &[TilePointer]) {
+    let mut grid = Grid::new(board);
+
+    for (i, &tile) in line.iter().enumerate() {
+      assert_eq!(grid.get(tile), None, "{tile} is occupied");
+
+      if i % 2 == 0 {
+        if i + 1 == line.len() {
+          assert!(grid.makes_five(tile, player), "{tile} doesn't win");
+        }
+        grid.set(tile, Some(player));
+      } else {
+        assert!(
+          grid.five_points(player).contains(&tile),
+          "{tile} isn't forced"
+        );
+        grid.set(tile, Some(!player));
+      }
+    }
+  }
+
+  #[test]
+  fn test_quiescence_overline_under_renju() {
+    // X at e5 can be completed only to overlines, at b5 and g5
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+x-xx-x-x-
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+    let tile = TilePointer { x: 4, y: 4 };
+
+    assert_eq!(
+      quiescence(&board, tile, Player::X, 4, Rules::freestyle()),
+      Some(3)
+    );
+    assert_eq!(quiescence(&board, tile, Player::X, 4, Rules::renju()), None);
+
+    // white wins with an overline even under Renju
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+o-oo-o-o-
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+    assert_eq!(
+      quiescence(&board, tile, Player::O, 4, Rules::renju()),
+      Some(3)
+    );
+  }
+
+  #[test]
+  fn test_find_vcf() {
+    let board = Board::from_str(VCF_BOARD).unwrap();
+
+    assert_eq!(find_vcf(&board, Player::X, 2), None);
+
+    let line = find_vcf(&board, Player::X, 3).expect("there is a VCF");
+
+    assert_eq!(line.len(), 5);
+    assert_forced_win(&board, Player::X, &line);
+  }
+
+  #[test]
+  fn test_solve() {
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+--xxx----
+---------
+---oo----
+---------
+---------",
+    )
+    .unwrap();
+
+    assert_eq!(solve(&board, Player::X, 2), None);
+
+    let line = solve(&board, Player::X, 3).expect("an open four wins");
+    assert_eq!(line.len(), 3);
+    assert_forced_win(&board, Player::X, &line);
+
+    let board = Board::from_str(VCF_BOARD).unwrap();
+
+    assert_eq!(solve(&board, Player::X, 3), None);
+
+    let line = solve(&board, Player::X, 5).expect("there is a VCF");
+    assert_eq!(line.len(), 5);
+    assert_forced_win(&board, Player::X, &line);
+  }
+
+  #[test]
+  fn test_solve_none() {
+    // the three is closed on one side, so a four gets blocked
+    let board = Board::from_str(
+      "---------
+---------
+---------
+---------
+oxxx-----
+---------
+---------
+---------
+---------",
+    )
+    .unwrap();
+
+    assert_eq!(solve(&board, Player::X, 3), None);
+    assert_eq!(solve(&board, Player::O, 3), None);
+  }
+
+  #[test]
+  fn test_find_vcf_none() {
+    let board = Board::from_str(VCF_BOARD).unwrap();
+
+    assert_eq!(find_vcf(&board, Player::O, 5), None);
+  }
+}
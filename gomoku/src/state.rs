@@ -1,25 +1,36 @@
 use std::fmt;
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// State of the game from the perspective of one player
 pub enum State {
+  /// The game goes on
   NotEnd,
+  /// The player has won
   Win,
+  /// The player has lost
   Lose,
+  /// Nobody can win anymore
   Draw,
 }
 impl State {
+  /// Whether the game has ended
   pub fn is_end(self) -> bool {
     !matches!(self, Self::NotEnd)
   }
 
+  /// Whether the player has won
   pub fn is_win(self) -> bool {
     matches!(self, Self::Win)
   }
 
+  /// Whether the player has lost
   pub fn is_lose(self) -> bool {
     matches!(self, Self::Lose)
   }
 
+  /// The same state from the perspective of the opponent
+  #[must_use]
   pub fn inversed(self) -> Self {
     match self {
       Self::NotEnd => Self::NotEnd,
@@ -2,24 +2,42 @@ use std::{
   fmt,
   iter::Sum,
   ops::{Add, AddAssign},
+  time::Duration,
 };
 
 use super::utils::format_number;
 
 /// Stats for the engine
 ///
-/// Currently only contains the number of nodes evaluated, but more can be added
-/// in the future.
-#[derive(Debug, Copy, Clone)]
+/// Stats of parts of a search, e.g. from different worker threads, are
+/// merged using [`Add`] or [`Sum`]. Merging is associative and commutative
+/// with [`Stats::new`] as the identity, so the result doesn't depend on the
+/// order the parts finish in. Counters are summed, while the depth is the
+/// deepest of the parts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct Stats {
   /// The number of nodes evaluated by the engine
   pub nodes_evaluated: u32,
+  /// The last depth the search fully completed
+  pub depth_reached: u8,
+  /// How long the search took
+  pub elapsed: Duration,
 }
 impl Stats {
   /// Create a new stats initialized to 0
   pub fn new() -> Stats {
-    Stats { nodes_evaluated: 0 }
+    Stats {
+      nodes_evaluated: 0,
+      depth_reached: 0,
+      elapsed: Duration::ZERO,
+    }
+  }
+
+  /// Get the number of nodes evaluated per second
+  pub fn nodes_per_second(&self) -> f32 {
+    self.nodes_evaluated as f32 / self.elapsed.as_secs_f32()
   }
 
   /// Increase the number of nodes evaluated by 1
@@ -34,22 +52,30 @@ impl Default for Stats {
   }
 }
 impl fmt::Display for Stats {
+  /// Write a one line summary in the style of engine info lines, e.g.
+  /// `depth 4 nodes 12.5k nps 50k time 250ms`.
   #[allow(clippy::cast_precision_loss)]
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     write!(
       f,
-      "Nodes evaluated: {} ({})",
-      self.nodes_evaluated,
-      format_number(self.nodes_evaluated as f32)
+      "depth {} nodes {} nps {} time {:?}",
+      self.depth_reached,
+      format_number(self.nodes_evaluated as f32),
+      format_number(self.nodes_per_second()),
+      self.elapsed
     )
   }
 }
 impl Add for Stats {
   type Output = Stats;
 
+  /// Merge stats of two searches, the depth is the deeper one and the time is
+  /// summed.
   fn add(self, other: Stats) -> Self::Output {
     Self {
-      nodes_evaluated: self.nodes_evaluated + other.nodes_evaluated,
+      nodes_evaluated: self.nodes_evaluated.saturating_add(other.nodes_evaluated),
+      depth_reached: self.depth_reached.max(other.depth_reached),
+      elapsed: self.elapsed + other.elapsed,
     }
   }
 }
@@ -66,3 +92,64 @@ impl Sum for Stats {
     iter.fold(Stats::new(), |acc, x| acc + x)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_add() {
+    let a = Stats {
+      nodes_evaluated: 10,
+      depth_reached: 3,
+      elapsed: Duration::from_millis(100),
+    };
+    let b = Stats {
+      nodes_evaluated: 30,
+      depth_reached: 5,
+      elapsed: Duration::from_millis(300),
+    };
+
+    let sum = [a, b].into_iter().sum::<Stats>();
+
+    assert_eq!(sum.nodes_evaluated, 40);
+    assert_eq!(sum.depth_reached, 5);
+    assert_eq!(sum.elapsed, Duration::from_millis(400));
+    assert!((sum.nodes_per_second() - 100.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn test_parallel_sum() {
+    use crate::parallel::prelude::*;
+
+    let parts = (0..100u8)
+      .map(|i| Stats {
+        nodes_evaluated: u32::from(i),
+        depth_reached: i % 7,
+        elapsed: Duration::from_millis(u64::from(i)),
+      })
+      .collect::<Vec<_>>();
+
+    let sequential = parts.iter().copied().sum::<Stats>();
+    let parallel = parts.clone().into_par_iter().sum::<Stats>();
+    let reversed = parts.iter().rev().copied().sum::<Stats>();
+
+    assert_eq!(sequential.nodes_evaluated, 4950);
+    assert_eq!(sequential.depth_reached, 6);
+    assert_eq!(parallel, sequential);
+    assert_eq!(reversed, sequential);
+    assert_eq!(sequential + Stats::new(), sequential);
+  }
+
+  #[test]
+  fn test_display() {
+    let stats = Stats {
+      nodes_evaluated: 12_500,
+      depth_reached: 4,
+      elapsed: Duration::from_millis(250),
+    };
+
+    assert_eq!(stats.to_string(), "depth 4 nodes 12.5k nps 50k time 250ms");
+    assert_eq!(Stats::new().to_string(), "depth 0 nodes 0 nps 0 time 0ns");
+  }
+}
@@ -1,27 +1,48 @@
 use std::{
-  sync::atomic::Ordering,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+  },
   time::{Duration, Instant},
 };
 
-/// Check if the engine should continue running.
+/// Cancellation flag for a single search.
 ///
-/// This is done by reading the `END` variable.
-#[inline]
-pub(crate) fn do_run() -> bool {
-  !END.load(Ordering::Acquire)
+/// Cloning the token shares the underlying flag, so the caller and the search
+/// workers all observe the same state.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CancelToken(Arc<AtomicBool>);
+impl CancelToken {
+  /// Create a new token that is not cancelled.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Signal the search to stop.
+  #[cfg_attr(not(feature = "threads"), allow(dead_code))]
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Release);
+  }
+
+  /// Check if the search was asked to stop.
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Acquire)
+  }
 }
 
-/// Print an engine status message to the console.
-///
-/// The format is: `<msg> (<time> remaining)`
-pub(crate) fn print_status(msg: &str, end_time: &Instant) {
-  println!(
-    "{} ({:?} remaining)",
-    msg,
-    (*end_time)
-      .checked_duration_since(Instant::now())
-      .unwrap_or(Duration::ZERO)
-  );
+/// Default clock of the search, time since its first call.
+pub(crate) fn monotonic_clock() -> Duration {
+  static START: OnceLock<Instant> = OnceLock::new();
+
+  START.get_or_init(Instant::now).elapsed()
+}
+
+/// Mix the bits of `x`, see <https://prng.di.unimi.it/splitmix64.c>.
+pub(crate) fn splitmix64(x: u64) -> u64 {
+  let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+  z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+  z ^ (z >> 31)
 }
 
 /// Format a number into a readable string with SI suffix.
@@ -38,11 +59,22 @@ pub(crate) fn print_status(msg: &str, end_time: &Instant) {
 /// assert_eq!(format_number(1250000.0), "1.25M");
 /// ```
 pub fn format_number(input: f32) -> String {
+  const SIZES: [char; 5] = ['-', 'k', 'M', 'G', 'T'];
+
+  if !input.is_finite() {
+    return "0".to_owned();
+  }
+
+  if input < 0.0 {
+    return format!("-{}", format_number(-input));
+  }
+
   let (number, i) = if input < 1.0 {
     (input, 0.0)
   } else {
     let base = 1000.0;
-    let i = input.log(base).floor();
+    // clamp to the largest suffix, bigger numbers just get more digits
+    let i = input.log(base).floor().min((SIZES.len() - 1) as f32);
     (input / base.powi(i as i32), i)
   };
 
@@ -52,15 +84,14 @@ pub fn format_number(input: f32) -> String {
     .to_owned();
 
   if i >= 1.0 {
-    let sizes = ['-', 'k', 'M', 'G', 'T'];
-    format!("{string}{}", sizes[i as usize])
+    format!("{string}{}", SIZES[i as usize])
   } else {
     string
   }
 }
 
 #[cfg(feature = "fen")]
-pub use fen::{parse_fen_string, to_fen_string};
+pub use fen::{parse_fen_string, parse_fen_with_state, to_fen_string, to_fen_with_state};
 
 #[cfg(feature = "fen")]
 mod fen {
@@ -68,7 +99,7 @@ mod fen {
 
   use regex::{Captures, Regex};
 
-  use crate::Board;
+  use crate::{Board, Player};
 
   /// Helper function for replacing all matches in a string using a replacement
   /// function
@@ -89,33 +120,54 @@ mod fen {
     Ok(new)
   }
 
-  /// Parses an shortened FEN string to full one
-  ///
-  /// Expects the input to be in the format `size|data`, where data is a string
-  /// of rows separated by `/` and each row contains `x`, `o`, `-` or a number
-  /// specifying the count of `-`.
-  ///
-  /// # Errors
-  /// Returns an error if the format is incorrect, size doesn't match the line
-  /// count or line length, or the data contains invalid characters.
-  #[allow(clippy::missing_panics_doc)] // https://github.com/rust-lang/rust-clippy/issues/11436
-  pub fn parse_fen_string(input: &str) -> Result<String, Box<dyn Error>> {
-    let input = input.trim();
+  /// Fields of a FEN string
+  struct Fields<'a> {
+    width: usize,
+    height: usize,
+    data: &'a str,
+    /// Side to move and ply, present only in the extended form
+    state: Option<(Player, u32)>,
+  }
 
-    let (prefix, data) = {
-      let splitted: Vec<_> = input.split('|').collect();
+  fn split_fields(input: &str) -> Result<Fields<'_>, Box<dyn Error>> {
+    let splitted: Vec<_> = input.trim().split('|').collect();
+
+    let (prefix, data, state) = match splitted[..] {
+      [prefix, data] => (prefix, data, None),
+      [prefix, data, side, ply] => {
+        let side = match side {
+          "x" => Player::X,
+          "o" => Player::O,
+          _ => return Err(format!("Invalid side to move {side:?}").into()),
+        };
+
+        (prefix, data, Some((side, ply.parse()?)))
+      },
+      _ => return Err("Incorrect format".into()),
+    };
 
-      match splitted[..] {
-        [prefix, data] => Ok((prefix, data)),
-        _ => Err("Incorrect format"),
-      }
-    }?;
+    // square boards have a single size, rectangular ones `WxH`
+    let (width, height) = if let Some((width, height)) = prefix.split_once('x') {
+      (width.parse()?, height.parse()?)
+    } else {
+      let size = prefix.parse()?;
+      (size, size)
+    };
 
-    let size = prefix.parse()?;
+    Ok(Fields {
+      width,
+      height,
+      data,
+      state,
+    })
+  }
 
+  /// Expand `height` rows of `width` tiles, joined by `/`
+  #[allow(clippy::missing_panics_doc)] // https://github.com/rust-lang/rust-clippy/issues/11436
+  fn expand_rows(width: usize, height: usize, data: &str) -> Result<String, Box<dyn Error>> {
     let parts: Vec<_> = data.split('/').collect();
 
-    if parts.len() != size {
+    if parts.len() != height {
       return Err("Incorrect row count".into());
     }
 
@@ -127,13 +179,13 @@ mod fen {
     };
 
     let parse_row = |part| -> Result<String, Box<dyn Error>> {
-      let parsed = replace_all(&re, part, replace_function)?.to_string();
+      let parsed = replace_all(&re, part, replace_function)?;
 
-      if parsed.len() > size {
+      if parsed.len() > width {
         return Err("Row too long".into());
       }
 
-      let padding = "-".repeat(size - parsed.len());
+      let padding = "-".repeat(width - parsed.len());
 
       Ok(parsed + &padding)
     };
@@ -145,6 +197,73 @@ mod fen {
       .map(|rows| rows.join("/"))
   }
 
+  /// Parses an shortened FEN string to full one
+  ///
+  /// Expects the input to be in the format `size|data`, where data is a string
+  /// of rows separated by `/` and each row contains `x`, `o`, `-` or a number
+  /// specifying the count of `-`. Rectangular boards use `WxH` instead of the
+  /// size, for example `15x9|data`.
+  ///
+  /// The extended form `size|data|side|ply` with the side to move (`x` or `o`)
+  /// and the move counter is accepted as well, the two fields are kept in the
+  /// output.
+  ///
+  /// # Errors
+  /// Returns an error if the format is incorrect, size doesn't match the line
+  /// count or line length, or the side to move or ply are invalid.
+  pub fn parse_fen_string(input: &str) -> Result<String, Box<dyn Error>> {
+    let Fields {
+      width,
+      height,
+      data,
+      state,
+    } = split_fields(input)?;
+
+    let rows = expand_rows(width, height, data)?;
+
+    Ok(match state {
+      Some((side, ply)) => format!("{rows}|{}|{ply}", side.char()),
+      None => rows,
+    })
+  }
+
+  /// Parses a FEN string into a board, the side to move and the ply
+  ///
+  /// Accepts both forms described in [`parse_fen_string`]. For the short form
+  /// the ply is the number of stones on the board and the side to move follows
+  /// from it, assuming `X` moved first.
+  ///
+  /// # Errors
+  /// Returns an error if the string is malformed or contains an invalid tile.
+  pub fn parse_fen_with_state(input: &str) -> Result<(Board, Player, u32), Box<dyn Error>> {
+    let Fields {
+      width,
+      height,
+      data,
+      state,
+    } = split_fields(input)?;
+
+    let rows = expand_rows(width, height, data)?
+      .split('/')
+      .map(|row| row.chars().collect())
+      .collect();
+
+    let board = Board::try_from_chars(rows)?;
+
+    let (side, ply) = state.unwrap_or_else(|| {
+      let ply = (board.stone_count(Player::X) + board.stone_count(Player::O)) as u32;
+      let side = if ply.is_multiple_of(2) {
+        Player::X
+      } else {
+        Player::O
+      };
+
+      (side, ply)
+    });
+
+    Ok((board, side, ply))
+  }
+
   /// Converts a board to a shortened FEN string
   #[must_use]
   #[allow(clippy::missing_panics_doc)] // https://github.com/rust-lang/rust-clippy/issues/11436
@@ -160,7 +279,7 @@ mod fen {
 
     let data = board
       .tiles()
-      .chunks(board.size() as usize)
+      .chunks(board.width() as usize)
       .map(|row| {
         row
           .iter()
@@ -174,35 +293,232 @@ mod fen {
       .collect::<Vec<_>>()
       .join("/");
 
-    format!("{}|{}", board.size(), data)
+    if board.width() == board.height() {
+      format!("{}|{data}", board.width())
+    } else {
+      format!("{}x{}|{data}", board.width(), board.height())
+    }
+  }
+
+  /// Converts a board to the extended FEN string including the side to move
+  /// and the ply
+  #[must_use]
+  pub fn to_fen_with_state(board: &Board, side: Player, ply: u32) -> String {
+    format!("{}|{}|{ply}", to_fen_string(board), side.char())
   }
 }
 
-use crate::{Board, Player, Score, END};
+pub use sgf::{parse_sgf, to_sgf};
+
+mod sgf {
+  use std::{error::Error, fmt::Write};
+
+  use crate::{Board, Player, TilePointer};
+
+  /// Board size assumed when the record doesn't contain the `SZ` property
+  const DEFAULT_SIZE: u8 = 15;
+
+  /// Board size and the list of played moves
+  type Record = (u8, Vec<(Player, TilePointer)>);
+
+  fn player_to_sgf(player: Player) -> char {
+    match player {
+      Player::X => 'B',
+      Player::O => 'W',
+    }
+  }
+
+  /// Largest board size the SGF coordinates can describe, `a` to `z` then
+  /// `A` to `Z`
+  const MAX_SIZE: u8 = 52;
+
+  fn coord_to_sgf(coord: u8) -> char {
+    debug_assert!(coord < MAX_SIZE);
+
+    if coord < 26 {
+      (b'a' + coord) as char
+    } else {
+      (b'A' + coord - 26) as char
+    }
+  }
+
+  fn coord_from_sgf(c: u8) -> Result<u8, Box<dyn Error>> {
+    match c {
+      b'a'..=b'z' => Ok(c - b'a'),
+      b'A'..=b'Z' => Ok(c - b'A' + 26),
+      _ => Err(format!("Invalid coordinate {:?}", c as char).into()),
+    }
+  }
+
+  /// Converts a list of moves to a SGF game record
+  ///
+  /// `X` is written as black (`B`) and `O` as white (`W`). Coordinates use the
+  /// SGF letter-letter scheme, column first, both starting at `a` and
+  /// continuing with `A` after `z`.
+  ///
+  /// # Errors
+  /// Returns an error if the board is larger than 52, which SGF can't
+  /// describe, or a move is outside of the board.
+  pub fn to_sgf(moves: &[(Player, TilePointer)], board_size: u8) -> Result<String, Box<dyn Error>> {
+    if board_size > MAX_SIZE {
+      return Err(format!("Board size {board_size} is larger than SGF allows ({MAX_SIZE})").into());
+    }
+
+    if let Some((_, tile)) = moves
+      .iter()
+      .find(|(_, t)| t.x >= board_size || t.y >= board_size)
+    {
+      return Err(format!("Move {tile} is outside of the board").into());
+    }
+
+    let mut sgf = format!("(;GM[4]FF[4]SZ[{board_size}]");
+
+    for (player, TilePointer { x, y }) in moves {
+      write!(
+        sgf,
+        ";{}[{}{}]",
+        player_to_sgf(*player),
+        coord_to_sgf(*x),
+        coord_to_sgf(*y)
+      )
+      .expect("writing to a String can't fail");
+    }
+
+    sgf.push(')');
+    Ok(sgf)
+  }
+
+  /// Parses a SGF game record into board size and a list of moves
+  ///
+  /// Only the `SZ`, `B` and `W` properties are interpreted, everything else is
+  /// skipped. If `SZ` is missing, size 15 is assumed.
+  ///
+  /// # Errors
+  /// Returns an error if the record is malformed, the size is outside of
+  /// [`Board::MIN_SIZE`] to 52, or it contains an invalid coordinate or a
+  /// move outside of the board.
+  pub fn parse_sgf(input: &str) -> Result<Record, Box<dyn Error>> {
+    let mut size = None;
+    let mut moves = Vec::new();
+
+    let mut chars = input.chars().peekable();
+    let mut ident = String::new();
+
+    while let Some(c) = chars.next() {
+      match c {
+        'A'..='Z' => ident.push(c),
+        '[' => {
+          let mut value = String::new();
+
+          loop {
+            match chars.next() {
+              Some('\\') => value.extend(chars.next()),
+              Some(']') => break,
+              Some(c) => value.push(c),
+              None => return Err("Unterminated property value".into()),
+            }
+          }
+
+          match ident.as_str() {
+            "SZ" => size = Some(value.trim().parse()?),
+            "B" | "W" => {
+              let player = if ident == "B" { Player::X } else { Player::O };
+
+              let [x, y] = value.as_bytes() else {
+                return Err(format!("Invalid move {value:?}").into());
+              };
+
+              let tile = TilePointer {
+                x: coord_from_sgf(*x)?,
+                y: coord_from_sgf(*y)?,
+              };
+
+              moves.push((player, tile));
+            },
+            "" => return Err("Property value without identifier".into()),
+            _ => {},
+          }
+
+          // the same property may have multiple values, e.g. `AB[aa][bb]`
+          if chars.peek() != Some(&'[') {
+            ident.clear();
+          }
+        },
+        c if c.is_whitespace() => {},
+        '(' | ')' | ';' => ident.clear(),
+        _ => return Err(format!("Unexpected character {c:?}").into()),
+      }
+    }
+
+    let size = size.unwrap_or(DEFAULT_SIZE);
+
+    if !(Board::MIN_SIZE..=MAX_SIZE).contains(&size) {
+      return Err(
+        format!(
+          "Board size {size} is outside of the supported range ({}-{MAX_SIZE})",
+          Board::MIN_SIZE
+        )
+        .into(),
+      );
+    }
+
+    if let Some((_, tile)) = moves.iter().find(|(_, t)| t.x >= size || t.y >= size) {
+      return Err(format!("Move {tile} is outside of the board").into());
+    }
+
+    Ok((size, moves))
+  }
+}
+
+use crate::{Board, Player, Score, Tile};
 
 /// Check if the game has ended.
 ///
-/// Iterate over all sequences and check if any of them is a win or loss for the
-/// current player.
+/// Check if the current player has five in a row. With the `bitboard` feature
+/// this is done on bit sets instead of walking the sequences.
 pub fn is_game_end(board: &Board, current_player: Player) -> bool {
-  fn is_game_end_sequence(sequence: &[usize], current_player: Player, board: &Board) -> bool {
-    sequence
-      .iter()
-      .scan(0, |consecutive, &idx| {
-        if *board.get_tile_raw(idx) == Some(current_player) {
-          *consecutive += 1;
-        } else {
-          *consecutive = 0;
-        }
-        Some(*consecutive)
-      })
-      .any(|consecutive| consecutive == 5)
-  }
+  #[cfg(feature = "bitboard")]
+  let end = board.has_five(current_player);
+  #[cfg(not(feature = "bitboard"))]
+  let end = is_game_end_by_scan(board, current_player);
+
+  end
+}
+
+/// Iterate over all sequences and check if any of them is a win for the
+/// current player.
+#[cfg_attr(all(feature = "bitboard", not(test)), allow(dead_code))]
+fn is_game_end_by_scan(board: &Board, current_player: Player) -> bool {
+  board.sequences().iter().any(|sequence| {
+    has_run(
+      sequence.iter().map(|&idx| *board.get_tile_raw(idx)),
+      current_player,
+      5,
+    )
+  })
+}
+
+/// Check if the line of tiles contains at least `win_length` consecutive
+/// stones of the player.
+///
+/// Longer runs count too, as overlines win in freestyle. Works on any line,
+/// e.g. a row cut out of a board, without needing a [`Board`].
+pub fn five_in_a_row(tiles: &[Tile], player: Player, win_length: u8) -> bool {
+  has_run(tiles.iter().copied(), player, win_length)
+}
 
-  board
-    .sequences()
-    .iter()
-    .any(|sequence| is_game_end_sequence(sequence, current_player, board))
+fn has_run(tiles: impl IntoIterator<Item = Tile>, player: Player, length: u8) -> bool {
+  tiles
+    .into_iter()
+    .scan(0, |consecutive, tile| {
+      if tile == Some(player) {
+        *consecutive += 1;
+      } else {
+        *consecutive = 0;
+      }
+      Some(*consecutive)
+    })
+    .any(|consecutive| consecutive >= length)
 }
 
 /// Calculate square root of the score and preserve the sign.
@@ -214,6 +530,178 @@ pub(crate) fn signed_sqrt(n: Score) -> Score {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::TilePointer;
+
+  #[cfg(feature = "bitboard")]
+  #[test]
+  fn test_bitboard_matches_scan() {
+    for (width, height) in [(9, 9), (15, 15), (20, 13)] {
+      let mut board = Board::new_rect(width, height).unwrap();
+      let mut tiles = board.pointers_to_empty_tiles().collect::<Vec<_>>();
+
+      // clustered stones, so there are plenty of fives in every direction
+      for step in 0..tiles.len() as u64 {
+        let random = splitmix64(step ^ u64::from(width)) as usize;
+        let ptr = tiles.swap_remove(random % tiles.len().min(40));
+        board.set_tile(ptr, Some(Player::all()[random / 64 % 2]));
+
+        for player in Player::all() {
+          assert_eq!(
+            is_game_end(&board, player),
+            is_game_end_by_scan(&board, player),
+            "{player} on board\n{board}"
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_five_in_a_row() {
+    let line = |s: &str| {
+      s.chars()
+        .map(|c| Player::from_char(c).ok())
+        .collect::<Vec<_>>()
+    };
+
+    assert!(five_in_a_row(&line("xxxxx"), Player::X, 5));
+    assert!(five_in_a_row(&line("-oxxxxxo-"), Player::X, 5));
+    assert!(!five_in_a_row(&line("-oxxxxxo-"), Player::O, 5));
+    assert!(!five_in_a_row(&line("xxxx-xxxx"), Player::X, 5));
+    assert!(!five_in_a_row(&line("xxxxoxxxx"), Player::X, 5));
+    assert!(!five_in_a_row(&line(""), Player::X, 5));
+
+    // overlines
+    assert!(five_in_a_row(&line("-xxxxxx-"), Player::X, 5));
+    assert!(five_in_a_row(&line("oooooooooo"), Player::O, 5));
+    assert!(!five_in_a_row(&line("-xxxxx-"), Player::X, 6));
+    assert!(five_in_a_row(&line("-xxxxxx-"), Player::X, 6));
+  }
+
+  #[test]
+  fn test_sgf_round_trip() {
+    let moves = vec![
+      (Player::X, TilePointer { x: 7, y: 7 }),
+      (Player::O, TilePointer { x: 8, y: 7 }),
+      (Player::X, TilePointer { x: 0, y: 14 }),
+    ];
+
+    let sgf = to_sgf(&moves, 15).unwrap();
+
+    assert_eq!(sgf, "(;GM[4]FF[4]SZ[15];B[hh];W[ih];B[ao])");
+    assert_eq!(parse_sgf(&sgf).unwrap(), (15, moves));
+
+    let wide = vec![
+      (Player::X, TilePointer { x: 29, y: 3 }),
+      (Player::O, TilePointer { x: 25, y: 26 }),
+      (Player::X, TilePointer { x: 51, y: 51 }),
+    ];
+
+    let sgf = to_sgf(&wide, 52).unwrap();
+
+    assert_eq!(sgf, "(;GM[4]FF[4]SZ[52];B[Dd];W[zA];B[ZZ])");
+    assert_eq!(parse_sgf(&sgf).unwrap(), (52, wide));
+
+    assert!(to_sgf(&[], 53).is_err());
+    assert!(to_sgf(&[(Player::X, TilePointer { x: 200, y: 0 })], 30).is_err());
+  }
+
+  #[test]
+  fn test_parse_sgf() {
+    let (size, moves) =
+      parse_sgf("(;GM[4]SZ[19]PB[Alice]C[comment \\] here]\n;B[pd];W[qd])").unwrap();
+
+    assert_eq!(size, 19);
+    assert_eq!(
+      moves,
+      vec![
+        (Player::X, TilePointer { x: 15, y: 3 }),
+        (Player::O, TilePointer { x: 16, y: 3 })
+      ]
+    );
+
+    assert!(parse_sgf("(;SZ[15];B[pd])").is_err());
+    assert!(parse_sgf("(;B[h])").is_err());
+    assert!(parse_sgf("(;B[hh]").is_ok());
+    assert!(parse_sgf("(;B[hh").is_err());
+
+    assert!(parse_sgf("(;SZ[3])").is_err());
+    assert!(parse_sgf("(;SZ[60])").is_err());
+    assert_eq!(parse_sgf("(;SZ[9])").unwrap(), (9, Vec::new()));
+  }
+
+  #[cfg(feature = "fen")]
+  #[test]
+  fn test_fen_round_trip() {
+    let mut board = Board::new_empty(15).unwrap();
+    board.set_tile(TilePointer { x: 7, y: 7 }, Some(Player::X));
+    board.set_tile(TilePointer { x: 8, y: 7 }, Some(Player::O));
+    board.set_tile(TilePointer { x: 0, y: 14 }, Some(Player::X));
+
+    let fen = to_fen_string(&board);
+
+    assert_eq!(fen, "15|///////7xo///////x");
+    assert_eq!(Board::from_fen(&fen).unwrap(), board);
+
+    assert!(Board::from_fen("3|x/a/").is_err());
+    assert!(Board::from_fen("3|x/o").is_err());
+  }
+
+  #[cfg(feature = "fen")]
+  #[test]
+  fn test_fen_round_trip_rectangular() {
+    let mut board = Board::new_rect(12, 9).unwrap();
+    board.set_tile(TilePointer { x: 11, y: 0 }, Some(Player::X));
+    board.set_tile(TilePointer { x: 3, y: 8 }, Some(Player::O));
+
+    let fen = to_fen_string(&board);
+
+    assert_eq!(fen, "12x9|11x////////3o");
+    assert_eq!(Board::from_fen(&fen).unwrap(), board);
+
+    let fen = to_fen_with_state(&board, Player::X, 2);
+    assert_eq!(parse_fen_with_state(&fen).unwrap(), (board, Player::X, 2));
+
+    // the rows have to match the dimensions
+    assert!(Board::from_fen("9x12|11x////////3o").is_err());
+  }
+
+  #[cfg(feature = "fen")]
+  #[test]
+  fn test_fen_with_state() {
+    let mut board = Board::new_empty(9).unwrap();
+    board.set_tile(TilePointer { x: 4, y: 4 }, Some(Player::X));
+
+    let fen = to_fen_with_state(&board, Player::O, 1);
+
+    assert_eq!(fen, "9|////4x////|o|1");
+    assert!(parse_fen_string(&fen)
+      .unwrap()
+      .ends_with("/----x----/---------/---------/---------/---------|o|1"));
+    assert_eq!(
+      parse_fen_with_state(&fen).unwrap(),
+      (board.clone(), Player::O, 1)
+    );
+
+    // the short form derives the state from the stones
+    assert_eq!(
+      parse_fen_with_state("9|////4x////").unwrap(),
+      (board, Player::O, 1)
+    );
+
+    assert!(parse_fen_string("9|////4x////|a|1").is_err());
+    assert!(parse_fen_string("9|////4x////|X|1").is_err());
+    assert!(parse_fen_string("9|////4x////|o").is_err());
+    assert!(parse_fen_string("9|////4x////|o|-1").is_err());
+  }
+
+  #[test]
+  fn test_format_number_edge_cases() {
+    assert_eq!(format_number(1e18), "1000000T");
+    assert_eq!(format_number(-1500.0), "-1.5k");
+    assert_eq!(format_number(f32::INFINITY), "0");
+    assert_eq!(format_number(f32::NAN), "0");
+  }
 
   #[test]
   fn test_signed_sqrt() {
@@ -0,0 +1,26 @@
+#![warn(clippy::pedantic)]
+
+//! Brain for the Gomocup tournament manager, talks the Piskvork protocol on
+//! stdin and stdout.
+
+use std::io::{self, BufRead};
+
+use gomoku_lib::protocol::Brain;
+
+fn main() {
+  let mut brain = Brain::new();
+
+  for line in io::stdin().lock().lines() {
+    let Ok(line) = line else {
+      break;
+    };
+
+    for response in brain.handle(&line) {
+      println!("{response}");
+    }
+
+    if brain.is_finished() {
+      break;
+    }
+  }
+}
@@ -2,13 +2,13 @@
 #![allow(clippy::similar_names)]
 
 use std::{
-  fs::File,
+  fs::{self, File},
   io::{self, prelude::Read},
   str::FromStr,
-  time::Instant,
+  time::Duration,
 };
 
-use gomoku_lib::{self, utils, Board, Move, Player, TilePointer};
+use gomoku_lib::{self, utils, Board, Engine, GomokuError, Player, SearchEvent, TilePointer};
 
 type Error = Box<dyn std::error::Error>;
 
@@ -38,7 +38,7 @@ fn main() {
     match utils::parse_fen_string(&string) {
       Ok(s) => println!("{s}"),
       Err(err) => println!("{err}"),
-    };
+    }
 
     return;
   }
@@ -47,20 +47,46 @@ fn main() {
     .value_of_t("threads")
     .unwrap_or_else(|_| num_cpus::get());
 
-  gomoku_lib::set_thread_count(threads).expect("main is run only once");
-
   let player = matches.value_of_t("player").unwrap_or(Player::O);
 
   let time_limit = matches.value_of_t("time").unwrap_or(1000);
   let board_size = matches.value_of_t("board").unwrap_or(15);
 
+  // in debug mode the search tree is written next to the input file
+  let dump_path = matches.value_of("debug").map(|path| format!("{path}.dot"));
+
+  let mut builder = Engine::builder()
+    .threads(threads)
+    .time_limit(Duration::from_millis(time_limit));
+
+  if dump_path.is_some() {
+    builder = builder.dump_tree(2);
+  }
+
+  let engine = match builder
+    .progress(move |event| match (event, &dump_path) {
+      (SearchEvent::Tree { dot }, Some(path)) => match fs::write(path, dot) {
+        Ok(()) => println!("Search tree written to {path}"),
+        Err(err) => println!("Error writing search tree: {err}"),
+      },
+      (event, _) => println!("{event}"),
+    })
+    .build()
+  {
+    Ok(engine) => engine,
+    Err(err) => {
+      println!("Error: {err}");
+      return;
+    },
+  };
+
   if let Some(path) = matches.value_of("debug") {
-    match run_debug(path, player, time_limit) {
+    match run_debug(path, &engine, player) {
       Ok(()) => println!("Done!"),
       Err(msg) => println!("Error: {msg}"),
     }
   } else {
-    run(player, time_limit, board_size);
+    run(&engine, player, board_size);
   }
 }
 
@@ -113,35 +139,42 @@ fn parse_args() -> clap::ArgMatches {
     .get_matches()
 }
 
-fn run_debug(path_to_input: &str, player: Player, time_limit: u64) -> Result<(), Error> {
+fn run_debug(path_to_input: &str, engine: &Engine, player: Player) -> Result<(), Error> {
   let input_string = load_input(path_to_input)?;
   let mut board = Board::from_str(&input_string)?;
 
   println!("{board}");
 
-  println!("Searching with max time {time_limit} ms\n");
+  println!(
+    "Searching with max time {} ms\n",
+    engine.time_limit().as_millis()
+  );
 
-  let start = Instant::now();
+  let result = engine.decide(&mut board, player);
 
-  let result = gomoku_lib::decide(&mut board, player, time_limit);
-  let run_time = start.elapsed().as_micros();
-
-  let (best_move, stats) = match result {
+  let outcome = match result {
     Ok(result) => result,
+    Err(GomokuError::GameEnd { winner }) => {
+      println!("{winner} has already won");
+      return Ok(());
+    },
     Err(err) => {
-      println!("Error occured: {err:?}");
+      println!("Error occured: {err}");
       return Ok(());
     },
   };
 
+  let Some(best_move) = outcome.best else {
+    println!("Board is full, it's a draw");
+    return Ok(());
+  };
+
   println!();
-  println!("{stats}");
+  println!("{}", outcome.stats);
+  println!("verdict: {:?}", outcome.verdict);
   println!();
-  println!("{board}");
-  let Move { tile, score } = best_move;
-  println!("{tile:?}, {score:?}");
-
-  print_runtime(run_time);
+  println!("{}", board.render_colored(board.last_move()));
+  println!("{best_move}");
 
   Ok(())
 }
@@ -153,23 +186,31 @@ fn load_input(path: &str) -> Result<String, Error> {
   Ok(contents)
 }
 
-fn run(mut player: Player, time_limit: u64, board_size: u8) {
+fn run(engine: &Engine, mut player: Player, board_size: u8) {
   use text_io::read;
-  let mut board = Board::new_empty(board_size);
+  let mut board = match Board::new_empty(board_size) {
+    Ok(board) => board,
+    Err(err) => {
+      println!("Error: {err}");
+      return;
+    },
+  };
 
   let prefix = '!';
   if player == Player::X {
-    let middle = board_size / 2;
-    let tile = TilePointer {
-      x: middle,
-      y: middle,
+    let tile = match engine.opening_move(&mut board, player) {
+      Ok(outcome) => outcome.best.expect("empty board has a move").tile,
+      Err(err) => {
+        println!("Error occured: {err}");
+        return;
+      },
     };
-    board.set_tile(tile, Some(player));
+
     println!("{prefix}{tile:?}");
     player = !player;
   }
 
-  println!("board:\n{board}");
+  println!("board:\n{}", board.render_colored(board.last_move()));
 
   loop {
     let line: String = read!("{}\n");
@@ -191,58 +232,52 @@ fn run(mut player: Player, time_limit: u64, board_size: u8) {
       continue;
     };
 
-    if board.get_tile(tile_ptr).is_some() {
-      println!("Tile already used");
+    if let Err(err) = board.place(tile_ptr, player) {
+      println!("Invalid move: {err}");
       continue;
     }
 
-    board.set_tile(tile_ptr, Some(player));
-
     if utils::is_game_end(&board, player) {
       println!("Engine loses!\n$");
-      println!("{board}");
+      println!("{}", board.render_colored(board.last_move()));
       break;
     }
 
     player = !player;
 
-    let start = Instant::now();
-    let result = gomoku_lib::decide(&mut board, player, time_limit);
-    let run_time = start.elapsed().as_micros();
+    let result = engine.decide(&mut board, player);
 
-    let unwrapped = match result {
+    let outcome = match result {
       Ok(result) => result,
+      Err(GomokuError::GameEnd { winner }) => {
+        println!("{winner} has already won!\n$");
+        break;
+      },
       Err(err) => {
-        println!("Error occured: {err:?}");
+        println!("Error occured: {err}");
         continue;
       },
     };
 
-    let (Move { tile, score }, stats) = unwrapped;
+    let stats = outcome.stats;
+
+    let Some(best_move) = outcome.best else {
+      println!("Draw!\n$");
+      break;
+    };
 
-    print_runtime(run_time);
     println!();
     println!("{stats}");
-    println!("score: {score:?}");
+    println!("best move: {best_move}");
     println!();
-    println!("board:\n{board}");
+    println!("board:\n{}", board.render_colored(board.last_move()));
 
     if utils::is_game_end(&board, player) {
       println!("Engine wins!\n$");
       break;
     }
 
-    println!("{prefix}{tile:?}");
+    println!("{prefix}{:?}", best_move.tile);
     player = !player;
   }
 }
-
-fn print_runtime(run_time: u128) {
-  if run_time < 10_000 {
-    println!("Time: {run_time} \u{03bc}s");
-  } else if run_time < 10_000_000 {
-    println!("Time: {} ms", run_time / 1000);
-  } else {
-    println!("Time: {} s", run_time / 1_000_000);
-  }
-}